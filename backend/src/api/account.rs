@@ -0,0 +1,19 @@
+use super::{
+    database::{delete_user_data, insert_audit_event},
+    models::AuditEvent,
+    redis::{delete_redis_account, get_owned_item_ids},
+};
+use crate::{AppError, AppState};
+use std::sync::Arc;
+
+pub async fn delete_account(state: Arc<AppState>, email: &str) -> Result<(), AppError> {
+    let item_ids = get_owned_item_ids(state.clone(), email).await?;
+
+    delete_user_data(state.clone(), email, &item_ids).await?;
+
+    delete_redis_account(state.clone(), email, &item_ids).await?;
+
+    insert_audit_event(state.clone(), email, AuditEvent::AccountDeleted).await?;
+
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use super::{
-    database::{convert_cdc_item, get_cdc_id},
+    database::{convert_cdc_item, delete_found_item, delete_questions, get_cdc_id},
     meilisearch::{add_items, delete_item},
     models::RedisAction,
-    redis::{decrement_items, remove_id, try_get},
+    redis::{decrement_items, remove_id, remove_item_fingerprint, try_get},
 };
 use crate::state::AppState;
 use async_trait::async_trait;
@@ -99,15 +99,24 @@ async fn handle_item_deletion(
 
     delete_item(state.meili_client.clone(), meili_index, id).await?;
 
+    delete_questions(state.clone(), id).await?;
+
+    delete_found_item(state.clone(), id).await?;
+
+    let email = match try_get(state.clone(), redis_deletion_name, &id.to_string()).await? {
+        Some(email) => email,
+        None => return Ok(()),
+    };
+
     decrement_items(
         state.redis_connection_manager.clone(),
         RedisAction::LockedItems.as_ref(),
-        &try_get(state.clone(), redis_deletion_name, &id.to_string())
-            .await?
-            .expect("item insertion misconfigured"),
+        &email,
     )
     .await?;
 
+    remove_item_fingerprint(state.clone(), &email, &id.to_string()).await?;
+
     remove_id(state.clone(), redis_deletion_name, &id.to_string()).await?;
 
     Ok(())
@@ -0,0 +1,40 @@
+use super::{
+    models::RedisAction,
+    sessions::get_cookie,
+    twofactor::{hash_code, verify_code},
+};
+use axum::http::{
+    Method,
+    header::{AUTHORIZATION, HeaderMap},
+};
+
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+pub fn get_csrf_token(key: &str, session_id: &str) -> String {
+    hash_code(key, session_id)
+}
+
+pub fn get_bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+pub fn is_state_changing(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+pub fn verify_csrf(key: &str, headers: &HeaderMap) -> bool {
+    let session_id = match get_cookie(headers, RedisAction::Session.as_ref()) {
+        Some(session_id) if !session_id.is_empty() => session_id,
+        _ => return false,
+    };
+
+    match headers.get(CSRF_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(token) => verify_code(key, &session_id, token),
+        None => false,
+    }
+}
@@ -1,25 +1,40 @@
 use super::{
     consumer::MeiliConsumerFactory,
+    email::spawn_email_task,
     models::{
-        Condition, CronItem, CronItemRow, Emoji, Item, ItemPayload, ItemRow, ItemType, Location,
-        RedisAccount,
+        AnswerPayload, AuditEvent, Condition, CronItem, CronItemRow, Draft, DraftPayload, DraftRow,
+        Emoji, Item, ItemPayload, ItemRow, ItemType, Location, Question, QuestionPayload,
+        QuestionRow, RedisAccount, ScheduledDraftRow,
     },
+    passkeys::get_credential_id,
+    redis::{get_publish_cursor, handle_draft_publication, set_publish_cursor},
     schema::{
         KEYSPACE,
-        columns::{items, users},
+        columns::{
+            audit_log, drafts, found_items, items, passkeys, questions, recovery_codes,
+            scheduled_drafts, users,
+        },
         tables,
     },
+    similarity::is_duplicate_item,
+    textbooks::get_textbook,
+    twofactor::hash_code,
     utilities::convert_i8_to_u8,
 };
 use crate::{error::AppError, state::AppState};
 use anyhow::Error as anyhowError;
-use chrono::{Duration as chronoDuration, NaiveDate, Utc};
+use chrono::{DateTime, Duration as chronoDuration, NaiveDate, Utc};
 use futures_util::future::RemoteHandle;
 use once_cell::sync::Lazy;
 use scylla::{
     client::{session::Session, session_builder::SessionBuilder},
-    response::{PagingState, query_result::FirstRowError::RowsEmpty},
+    response::{
+        PagingState,
+        query_result::{FirstRowError::RowsEmpty, QueryResult},
+    },
+    serialize::row::SerializeRow,
     statement::{batch::Batch, prepared::PreparedStatement, unprepared::Statement},
+    value::{CqlValue, Row},
 };
 use scylla_cdc::{
     checkpoints::TableBackedCheckpointSaver,
@@ -28,8 +43,9 @@ use scylla_cdc::{
 };
 use std::{env, ops::ControlFlow, sync::Arc, time::Duration};
 use tokio_cron_scheduler::{Job, JobScheduler};
-use tracing::warn;
+use tracing::{debug, warn};
 use uuid::Uuid;
+use webauthn_rs::prelude::Passkey;
 
 #[derive(Clone)]
 pub struct DatabaseQueries {
@@ -38,12 +54,49 @@ pub struct DatabaseQueries {
     pub check_lock: PreparedStatement,
     pub update_lock: PreparedStatement,
     pub unlock_account: PreparedStatement,
+    pub update_password: PreparedStatement,
+    pub claim_user: PreparedStatement,
+    pub release_user: PreparedStatement,
+    pub delete_user: PreparedStatement,
+    pub get_totp_secret: PreparedStatement,
+    pub update_totp_secret: PreparedStatement,
+    pub get_recovery_codes: PreparedStatement,
+    pub insert_recovery_code: PreparedStatement,
+    pub delete_recovery_code: PreparedStatement,
+    pub get_mute_notices: PreparedStatement,
+    pub update_mute_notices: PreparedStatement,
     pub insert_item: PreparedStatement,
     pub get_items: PreparedStatement,
+    pub get_item: PreparedStatement,
     pub delete_item: PreparedStatement,
     pub get_cron_items: PreparedStatement,
+    pub insert_draft: PreparedStatement,
+    pub get_drafts: PreparedStatement,
+    pub get_draft: PreparedStatement,
+    pub delete_draft: PreparedStatement,
+    pub insert_scheduled_draft: PreparedStatement,
+    pub get_scheduled_drafts: PreparedStatement,
+    pub delete_scheduled_draft: PreparedStatement,
+    pub insert_question: PreparedStatement,
+    pub get_questions: PreparedStatement,
+    pub check_question: PreparedStatement,
+    pub answer_question: PreparedStatement,
+    pub delete_questions: PreparedStatement,
+    pub get_asked_questions: PreparedStatement,
+    pub delete_question: PreparedStatement,
+    pub migrate_question: PreparedStatement,
+    pub insert_found_item: PreparedStatement,
+    pub get_found_item: PreparedStatement,
+    pub delete_found_item: PreparedStatement,
+    pub insert_passkey: PreparedStatement,
+    pub get_passkeys: PreparedStatement,
+    pub delete_passkeys: PreparedStatement,
+    pub delete_drafts: PreparedStatement,
+    pub insert_audit_event: PreparedStatement,
 }
 
+const RECOVERY_CODE_WRITE_ATTEMPTS: usize = 3;
+
 static BASE_DATE: Lazy<NaiveDate> = Lazy::new(|| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
 
 pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError> {
@@ -71,6 +124,8 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                 {} {},
                 {} {},
                 {} {},
+                {} {},
+                {} {},
                 PRIMARY KEY({})
             )",
                 KEYSPACE,
@@ -81,6 +136,10 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                 users::PASSWORD_HASH_TYPE,
                 users::LOCKED,
                 users::LOCKED_TYPE,
+                users::TOTP_SECRET,
+                users::TOTP_SECRET_TYPE,
+                users::MUTE_NOTICES,
+                users::MUTE_NOTICES_TYPE,
                 users::PRIMARY_KEY,
             ),
             &[],
@@ -99,6 +158,9 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                 {} {},
                 {} {},
                 {} {},
+                {} {},
+                {} {},
+                {} {},
                 PRIMARY KEY({})
             ) WITH cdc = {{'enabled': true}}",
                 KEYSPACE,
@@ -119,12 +181,258 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                 items::EMOJI_TYPE,
                 items::EXPIRATION_DATE,
                 items::EXPIRATION_DATE_TYPE,
+                items::ISBN,
+                items::ISBN_TYPE,
+                items::AUTHOR,
+                items::AUTHOR_TYPE,
+                items::COURSE_CODE,
+                items::COURSE_CODE_TYPE,
                 items::PRIMARY_KEY,
             ),
             &[],
         )
         .await?;
 
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} (
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                PRIMARY KEY(({}), {})
+            )",
+                KEYSPACE,
+                tables::DRAFTS,
+                drafts::EMAIL,
+                drafts::EMAIL_TYPE,
+                drafts::DRAFT_ID,
+                drafts::DRAFT_ID_TYPE,
+                drafts::ITEM_TYPE,
+                drafts::ITEM_TYPE_TYPE,
+                drafts::TITLE,
+                drafts::TITLE_TYPE,
+                drafts::CONDITION,
+                drafts::CONDITION_TYPE,
+                drafts::LOCATION,
+                drafts::LOCATION_TYPE,
+                drafts::DESCRIPTION,
+                drafts::DESCRIPTION_TYPE,
+                drafts::EMOJI,
+                drafts::EMOJI_TYPE,
+                drafts::PUBLISH_AT,
+                drafts::PUBLISH_AT_TYPE,
+                drafts::ISBN,
+                drafts::ISBN_TYPE,
+                drafts::COURSE_CODE,
+                drafts::COURSE_CODE_TYPE,
+                drafts::BLOCKED,
+                drafts::BLOCKED_TYPE,
+                drafts::PARTITION_KEY,
+                drafts::CLUSTERING_KEY,
+            ),
+            &[],
+        )
+        .await?;
+
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} (
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                PRIMARY KEY(({}), {}, {}, {})
+            )",
+                KEYSPACE,
+                tables::SCHEDULED_DRAFTS,
+                scheduled_drafts::PUBLISH_BUCKET,
+                scheduled_drafts::PUBLISH_BUCKET_TYPE,
+                scheduled_drafts::PUBLISH_AT,
+                scheduled_drafts::PUBLISH_AT_TYPE,
+                scheduled_drafts::EMAIL,
+                scheduled_drafts::EMAIL_TYPE,
+                scheduled_drafts::DRAFT_ID,
+                scheduled_drafts::DRAFT_ID_TYPE,
+                scheduled_drafts::PARTITION_KEY,
+                scheduled_drafts::PUBLISH_AT,
+                scheduled_drafts::EMAIL,
+                scheduled_drafts::DRAFT_ID,
+            ),
+            &[],
+        )
+        .await?;
+
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} (
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                PRIMARY KEY(({}), {})
+            )",
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::ITEM_ID,
+                questions::ITEM_ID_TYPE,
+                questions::QUESTION_ID,
+                questions::QUESTION_ID_TYPE,
+                questions::EMAIL,
+                questions::EMAIL_TYPE,
+                questions::QUESTION,
+                questions::QUESTION_TYPE,
+                questions::ANSWER,
+                questions::ANSWER_TYPE,
+                questions::ASKED_AT,
+                questions::ASKED_AT_TYPE,
+                questions::PARTITION_KEY,
+                questions::CLUSTERING_KEY,
+            ),
+            &[],
+        )
+        .await?;
+
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} (
+                {} {},
+                {} {},
+                {} {},
+                PRIMARY KEY({})
+            )",
+                KEYSPACE,
+                tables::FOUND_ITEMS,
+                found_items::ITEM_ID,
+                found_items::ITEM_ID_TYPE,
+                found_items::QUESTIONS,
+                found_items::QUESTIONS_TYPE,
+                found_items::ANSWER_HASHES,
+                found_items::ANSWER_HASHES_TYPE,
+                found_items::PRIMARY_KEY,
+            ),
+            &[],
+        )
+        .await?;
+
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} (
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                PRIMARY KEY(({}), {})
+            )",
+                KEYSPACE,
+                tables::PASSKEYS,
+                passkeys::EMAIL,
+                passkeys::EMAIL_TYPE,
+                passkeys::CREDENTIAL_ID,
+                passkeys::CREDENTIAL_ID_TYPE,
+                passkeys::PASSKEY,
+                passkeys::PASSKEY_TYPE,
+                passkeys::CREATED_AT,
+                passkeys::CREATED_AT_TYPE,
+                passkeys::PARTITION_KEY,
+                passkeys::CLUSTERING_KEY,
+            ),
+            &[],
+        )
+        .await?;
+
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} (
+                {} {},
+                {} {},
+                {} {},
+                {} {},
+                PRIMARY KEY(({}), {})
+            )",
+                KEYSPACE,
+                tables::AUDIT_LOG,
+                audit_log::SUBJECT,
+                audit_log::SUBJECT_TYPE,
+                audit_log::EVENT_ID,
+                audit_log::EVENT_ID_TYPE,
+                audit_log::EVENT,
+                audit_log::EVENT_TYPE,
+                audit_log::CREATED_AT,
+                audit_log::CREATED_AT_TYPE,
+                audit_log::PARTITION_KEY,
+                audit_log::CLUSTERING_KEY,
+            ),
+            &[],
+        )
+        .await?;
+
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} (
+                {} {},
+                {} {},
+                PRIMARY KEY(({}), {})
+            )",
+                KEYSPACE,
+                tables::RECOVERY_CODES,
+                recovery_codes::EMAIL,
+                recovery_codes::EMAIL_TYPE,
+                recovery_codes::CODE_HASH,
+                recovery_codes::CODE_HASH_TYPE,
+                recovery_codes::PARTITION_KEY,
+                recovery_codes::CLUSTERING_KEY,
+            ),
+            &[],
+        )
+        .await?;
+
+    database_session
+        .query_unpaged(
+            format!(
+                "CREATE INDEX IF NOT EXISTS ON {}.{} ({})",
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::EMAIL
+            ),
+            &[],
+        )
+        .await?;
+
+    for (table, column, column_type) in [
+        (tables::USERS, users::TOTP_SECRET, users::TOTP_SECRET_TYPE),
+        (tables::USERS, users::MUTE_NOTICES, users::MUTE_NOTICES_TYPE),
+        (tables::ITEMS, items::ISBN, items::ISBN_TYPE),
+        (tables::ITEMS, items::AUTHOR, items::AUTHOR_TYPE),
+        (tables::ITEMS, items::COURSE_CODE, items::COURSE_CODE_TYPE),
+        (tables::DRAFTS, drafts::ISBN, drafts::ISBN_TYPE),
+        (
+            tables::DRAFTS,
+            drafts::COURSE_CODE,
+            drafts::COURSE_CODE_TYPE,
+        ),
+        (tables::DRAFTS, drafts::BLOCKED, drafts::BLOCKED_TYPE),
+    ] {
+        add_column(&database_session, table, column, column_type).await?;
+    }
+
     let database_queries = DatabaseQueries {
         get_user: database_session
             .prepare(format!(
@@ -175,9 +483,111 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                 users::PRIMARY_KEY
             ))
             .await?,
+        update_password: database_session
+            .prepare(format!(
+                "UPDATE {}.{} SET {} = ? WHERE {} = ?",
+                KEYSPACE,
+                tables::USERS,
+                users::PASSWORD_HASH,
+                users::PRIMARY_KEY
+            ))
+            .await?,
+        claim_user: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?) IF NOT EXISTS USING TTL {}",
+                KEYSPACE,
+                tables::USERS,
+                users::EMAIL,
+                users::PASSWORD_HASH,
+                users::LOCKED,
+                users::TOTP_SECRET,
+                users::MUTE_NOTICES,
+                users::TTL
+            ))
+            .await?,
+        release_user: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ? IF EXISTS",
+                KEYSPACE,
+                tables::USERS,
+                users::PRIMARY_KEY
+            ))
+            .await?,
+        delete_user: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ?",
+                KEYSPACE,
+                tables::USERS,
+                users::PRIMARY_KEY
+            ))
+            .await?,
+        get_totp_secret: database_session
+            .prepare(format!(
+                "SELECT {} FROM {}.{} WHERE {} = ?",
+                users::TOTP_SECRET,
+                KEYSPACE,
+                tables::USERS,
+                users::PRIMARY_KEY
+            ))
+            .await?,
+        update_totp_secret: database_session
+            .prepare(format!(
+                "UPDATE {}.{} SET {} = ? WHERE {} = ?",
+                KEYSPACE,
+                tables::USERS,
+                users::TOTP_SECRET,
+                users::PRIMARY_KEY
+            ))
+            .await?,
+        get_recovery_codes: database_session
+            .prepare(format!(
+                "SELECT {} FROM {}.{} WHERE {} = ?",
+                recovery_codes::CODE_HASH,
+                KEYSPACE,
+                tables::RECOVERY_CODES,
+                recovery_codes::PARTITION_KEY
+            ))
+            .await?,
+        insert_recovery_code: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}) VALUES (?, ?) IF NOT EXISTS USING TTL {}",
+                KEYSPACE,
+                tables::RECOVERY_CODES,
+                recovery_codes::EMAIL,
+                recovery_codes::CODE_HASH,
+                recovery_codes::TTL
+            ))
+            .await?,
+        delete_recovery_code: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ? AND {} = ? IF EXISTS",
+                KEYSPACE,
+                tables::RECOVERY_CODES,
+                recovery_codes::PARTITION_KEY,
+                recovery_codes::CLUSTERING_KEY
+            ))
+            .await?,
+        get_mute_notices: database_session
+            .prepare(format!(
+                "SELECT {} FROM {}.{} WHERE {} = ?",
+                users::MUTE_NOTICES,
+                KEYSPACE,
+                tables::USERS,
+                users::PRIMARY_KEY
+            ))
+            .await?,
+        update_mute_notices: database_session
+            .prepare(format!(
+                "UPDATE {}.{} SET {} = ? WHERE {} = ?",
+                KEYSPACE,
+                tables::USERS,
+                users::MUTE_NOTICES,
+                users::PRIMARY_KEY
+            ))
+            .await?,
         insert_item: database_session
             .prepare(format!(
-                "INSERT INTO {}.{} ({}, {}, {}, {}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?, ?, ?, ?) USING TTL ?",
+                "INSERT INTO {}.{} ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) USING TTL ?",
                 KEYSPACE,
                 tables::ITEMS,
                 items::ITEM_ID,
@@ -188,12 +598,15 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                 items::DESCRIPTION,
                 items::EMOJI,
                 items::EXPIRATION_DATE,
+                items::ISBN,
+                items::AUTHOR,
+                items::COURSE_CODE,
             ))
             .await?,
         get_items: database_session
             .prepare(
                 Statement::new(format!(
-                    "SELECT {}, {}, {}, {}, {}, {}, {}, {} FROM {}.{}", 
+                    "SELECT {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {} FROM {}.{}", 
                     items::ITEM_ID,
                     items::ITEM_TYPE,
                     items::TITLE,
@@ -202,11 +615,33 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                     items::DESCRIPTION,
                     items::EMOJI,
                     items::EXPIRATION_DATE,
+                    items::ISBN,
+                    items::AUTHOR,
+                    items::COURSE_CODE,
                     KEYSPACE,
                     tables::ITEMS
                 )).with_page_size(100),
             )
             .await?,
+        get_item: database_session
+            .prepare(format!(
+                "SELECT {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {} FROM {}.{} WHERE {} = ?",
+                items::ITEM_ID,
+                items::ITEM_TYPE,
+                items::TITLE,
+                items::CONDITION,
+                items::LOCATION,
+                items::DESCRIPTION,
+                items::EMOJI,
+                items::EXPIRATION_DATE,
+                items::ISBN,
+                items::AUTHOR,
+                items::COURSE_CODE,
+                KEYSPACE,
+                tables::ITEMS,
+                items::PRIMARY_KEY
+            ))
+            .await?,
         get_cron_items: database_session
             .prepare(
                 Statement::new(format!(
@@ -226,42 +661,1164 @@ pub async fn init_database() -> Result<(Arc<Session>, DatabaseQueries), AppError
                 items::ITEM_ID,
             ))
             .await?,
-    };
-
-    Ok((Arc::new(database_session), database_queries))
+        insert_draft: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) USING TTL {}",
+                KEYSPACE,
+                tables::DRAFTS,
+                drafts::EMAIL,
+                drafts::DRAFT_ID,
+                drafts::ITEM_TYPE,
+                drafts::TITLE,
+                drafts::CONDITION,
+                drafts::LOCATION,
+                drafts::DESCRIPTION,
+                drafts::EMOJI,
+                drafts::PUBLISH_AT,
+                drafts::ISBN,
+                drafts::COURSE_CODE,
+                drafts::BLOCKED,
+                drafts::TTL
+            ))
+            .await?,
+        get_drafts: database_session
+            .prepare(format!(
+                "SELECT {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {} FROM {}.{} WHERE {} = ?",
+                drafts::DRAFT_ID,
+                drafts::ITEM_TYPE,
+                drafts::TITLE,
+                drafts::CONDITION,
+                drafts::LOCATION,
+                drafts::DESCRIPTION,
+                drafts::EMOJI,
+                drafts::PUBLISH_AT,
+                drafts::ISBN,
+                drafts::COURSE_CODE,
+                drafts::BLOCKED,
+                KEYSPACE,
+                tables::DRAFTS,
+                drafts::PARTITION_KEY
+            ))
+            .await?,
+        get_draft: database_session
+            .prepare(format!(
+                "SELECT {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {} FROM {}.{} WHERE {} = ? AND {} = ?",
+                drafts::DRAFT_ID,
+                drafts::ITEM_TYPE,
+                drafts::TITLE,
+                drafts::CONDITION,
+                drafts::LOCATION,
+                drafts::DESCRIPTION,
+                drafts::EMOJI,
+                drafts::PUBLISH_AT,
+                drafts::ISBN,
+                drafts::COURSE_CODE,
+                drafts::BLOCKED,
+                KEYSPACE,
+                tables::DRAFTS,
+                drafts::PARTITION_KEY,
+                drafts::CLUSTERING_KEY
+            ))
+            .await?,
+        delete_draft: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ? AND {} = ?",
+                KEYSPACE,
+                tables::DRAFTS,
+                drafts::PARTITION_KEY,
+                drafts::CLUSTERING_KEY
+            ))
+            .await?,
+        insert_scheduled_draft: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}, {}, {}) VALUES (?, ?, ?, ?) USING TTL {}",
+                KEYSPACE,
+                tables::SCHEDULED_DRAFTS,
+                scheduled_drafts::PUBLISH_BUCKET,
+                scheduled_drafts::PUBLISH_AT,
+                scheduled_drafts::EMAIL,
+                scheduled_drafts::DRAFT_ID,
+                scheduled_drafts::TTL
+            ))
+            .await?,
+        get_scheduled_drafts: database_session
+            .prepare(
+                Statement::new(format!(
+                    "SELECT {}, {}, {} FROM {}.{} WHERE {} = ? AND {} <= ?",
+                    scheduled_drafts::PUBLISH_AT,
+                    scheduled_drafts::EMAIL,
+                    scheduled_drafts::DRAFT_ID,
+                    KEYSPACE,
+                    tables::SCHEDULED_DRAFTS,
+                    scheduled_drafts::PARTITION_KEY,
+                    scheduled_drafts::PUBLISH_AT
+                )).with_page_size(100),
+            )
+            .await?,
+        delete_scheduled_draft: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ? AND {} = ? AND {} = ? AND {} = ?",
+                KEYSPACE,
+                tables::SCHEDULED_DRAFTS,
+                scheduled_drafts::PARTITION_KEY,
+                scheduled_drafts::PUBLISH_AT,
+                scheduled_drafts::EMAIL,
+                scheduled_drafts::DRAFT_ID
+            ))
+            .await?,
+        insert_question: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?) USING TTL {}",
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::ITEM_ID,
+                questions::QUESTION_ID,
+                questions::EMAIL,
+                questions::QUESTION,
+                questions::ASKED_AT,
+                questions::TTL
+            ))
+            .await?,
+        get_questions: database_session
+            .prepare(format!(
+                "SELECT {}, {}, {}, {} FROM {}.{} WHERE {} = ?",
+                questions::QUESTION_ID,
+                questions::QUESTION,
+                questions::ANSWER,
+                questions::ASKED_AT,
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::PARTITION_KEY
+            ))
+            .await?,
+        check_question: database_session
+            .prepare(format!(
+                "SELECT {} FROM {}.{} WHERE {} = ? AND {} = ?",
+                questions::EMAIL,
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::PARTITION_KEY,
+                questions::CLUSTERING_KEY
+            ))
+            .await?,
+        answer_question: database_session
+            .prepare(format!(
+                "UPDATE {}.{} USING TTL {} SET {} = ? WHERE {} = ? AND {} = ?",
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::TTL,
+                questions::ANSWER,
+                questions::PARTITION_KEY,
+                questions::CLUSTERING_KEY
+            ))
+            .await?,
+        delete_questions: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ?",
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::PARTITION_KEY
+            ))
+            .await?,
+        get_asked_questions: database_session
+            .prepare(
+                Statement::new(format!(
+                    "SELECT {}, {}, TTL({}) FROM {}.{} WHERE {} = ?",
+                    questions::ITEM_ID,
+                    questions::QUESTION_ID,
+                    questions::QUESTION,
+                    KEYSPACE,
+                    tables::QUESTIONS,
+                    questions::EMAIL
+                ))
+                .with_page_size(100),
+            )
+            .await?,
+        delete_question: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ? AND {} = ?",
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::PARTITION_KEY,
+                questions::CLUSTERING_KEY
+            ))
+            .await?,
+        migrate_question: database_session
+            .prepare(format!(
+                "UPDATE {}.{} USING TTL ? SET {} = ? WHERE {} = ? AND {} = ?",
+                KEYSPACE,
+                tables::QUESTIONS,
+                questions::EMAIL,
+                questions::PARTITION_KEY,
+                questions::CLUSTERING_KEY
+            ))
+            .await?,
+        insert_found_item: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}, {}) VALUES (?, ?, ?) USING TTL ?",
+                KEYSPACE,
+                tables::FOUND_ITEMS,
+                found_items::ITEM_ID,
+                found_items::QUESTIONS,
+                found_items::ANSWER_HASHES,
+            ))
+            .await?,
+        get_found_item: database_session
+            .prepare(format!(
+                "SELECT {}, {} FROM {}.{} WHERE {} = ?",
+                found_items::QUESTIONS,
+                found_items::ANSWER_HASHES,
+                KEYSPACE,
+                tables::FOUND_ITEMS,
+                found_items::PRIMARY_KEY
+            ))
+            .await?,
+        delete_found_item: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ?",
+                KEYSPACE,
+                tables::FOUND_ITEMS,
+                found_items::PRIMARY_KEY
+            ))
+            .await?,
+        insert_passkey: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}, {}, {}) VALUES (?, ?, ?, ?) USING TTL {}",
+                KEYSPACE,
+                tables::PASSKEYS,
+                passkeys::EMAIL,
+                passkeys::CREDENTIAL_ID,
+                passkeys::PASSKEY,
+                passkeys::CREATED_AT,
+                passkeys::TTL
+            ))
+            .await?,
+        get_passkeys: database_session
+            .prepare(format!(
+                "SELECT {}, {} FROM {}.{} WHERE {} = ?",
+                passkeys::PASSKEY,
+                passkeys::CREATED_AT,
+                KEYSPACE,
+                tables::PASSKEYS,
+                passkeys::PARTITION_KEY
+            ))
+            .await?,
+        delete_passkeys: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ?",
+                KEYSPACE,
+                tables::PASSKEYS,
+                passkeys::PARTITION_KEY
+            ))
+            .await?,
+        delete_drafts: database_session
+            .prepare(format!(
+                "DELETE FROM {}.{} WHERE {} = ?",
+                KEYSPACE,
+                tables::DRAFTS,
+                drafts::PARTITION_KEY
+            ))
+            .await?,
+        insert_audit_event: database_session
+            .prepare(format!(
+                "INSERT INTO {}.{} ({}, {}, {}, {}) VALUES (?, ?, ?, ?) USING TTL {}",
+                KEYSPACE,
+                tables::AUDIT_LOG,
+                audit_log::SUBJECT,
+                audit_log::EVENT_ID,
+                audit_log::EVENT,
+                audit_log::CREATED_AT,
+                audit_log::TTL
+            ))
+            .await?,
+    };
+
+    Ok((Arc::new(database_session), database_queries))
+}
+
+pub async fn get_user(
+    state: Arc<AppState>,
+    email: &str,
+) -> Result<Option<(String, bool)>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_user,
+            (email,),
+            fallback_page_state,
+        )
+        .await?;
+
+    match returned_rows
+        .into_rows_result()?
+        .first_row::<(String, bool)>()
+    {
+        Ok((password_hash, locked)) => Ok(Some((password_hash, locked))),
+        Err(RowsEmpty) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn insert_user(state: Arc<AppState>, account: RedisAccount) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.insert_user,
+            (account.email, account.password_hash, false),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn claim_user(
+    state: Arc<AppState>,
+    email: &str,
+    new_email: &str,
+) -> Result<bool, AppError> {
+    let (password_hash, locked) = match get_user(state.clone(), email).await? {
+        Some(user) => user,
+        None => return Ok(false),
+    };
+
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.claim_user,
+            (
+                new_email,
+                password_hash,
+                locked,
+                get_totp_secret(state.clone(), email).await?,
+                get_mute_notices(state.clone(), email).await?,
+            ),
+            fallback_page_state,
+        )
+        .await?;
+
+    is_applied(returned_rows)
+}
+
+pub async fn release_user(state: Arc<AppState>, new_email: &str) -> Result<(), AppError> {
+    replace_recovery_codes(state.clone(), new_email, &[]).await?;
+
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.release_user,
+            (new_email,),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn migrate_user(
+    state: Arc<AppState>,
+    email: &str,
+    new_email: &str,
+) -> Result<(), AppError> {
+    replace_recovery_codes(
+        state.clone(),
+        new_email,
+        &get_recovery_codes(state.clone(), email).await?,
+    )
+    .await?;
+
+    let mut batch: Batch = Default::default();
+    let mut batch_values: BatchRows = Vec::new();
+
+    for (passkey, created_at) in get_passkey_rows(state.clone(), email).await? {
+        batch.append_statement(state.database_queries.insert_passkey.clone());
+        batch_values.push(Box::new((
+            new_email.to_string(),
+            get_credential_id(passkey.cred_id()),
+            serde_json::to_string(&passkey)?,
+            created_at.unwrap_or_else(Utc::now),
+        )));
+    }
+
+    for draft in get_drafts(state.clone(), email).await? {
+        if let Some(publish_at) = draft.publish_at {
+            batch.append_statement(state.database_queries.delete_scheduled_draft.clone());
+            batch_values.push(Box::new((
+                get_publish_bucket(publish_at),
+                publish_at,
+                email.to_string(),
+                draft.draft_id,
+            )));
+        }
+
+        append_draft(&state, &mut batch, &mut batch_values, new_email, draft);
+    }
+
+    for (item_id, question_id, ttl) in get_asked_questions(state.clone(), email).await? {
+        batch.append_statement(state.database_queries.migrate_question.clone());
+        batch_values.push(Box::new((
+            ttl.unwrap_or(0),
+            new_email.to_string(),
+            item_id,
+            question_id,
+        )));
+    }
+
+    for statement in [
+        &state.database_queries.delete_user,
+        &state.database_queries.delete_drafts,
+        &state.database_queries.delete_passkeys,
+    ] {
+        batch.append_statement(statement.clone());
+        batch_values.push(Box::new((email.to_string(),)));
+    }
+
+    state.database_session.batch(&batch, &batch_values).await?;
+
+    if let Err(e) = replace_recovery_codes(state.clone(), email, &[]).await {
+        warn!("Failed to remove migrated recovery codes: {}", e);
+    }
+
+    Ok(())
+}
+
+pub async fn delete_user_data(
+    state: Arc<AppState>,
+    email: &str,
+    item_ids: &[Uuid],
+) -> Result<(), AppError> {
+    let mut item_batch: Batch = Default::default();
+    let mut item_batch_values = Vec::new();
+
+    for item_id in item_ids {
+        item_batch.append_statement(state.database_queries.delete_item.clone());
+
+        item_batch_values.push((item_id,));
+    }
+
+    if !item_batch_values.is_empty() {
+        state
+            .database_session
+            .batch(&item_batch, &item_batch_values)
+            .await?;
+    }
+
+    let mut question_batch: Batch = Default::default();
+    let question_batch_values: Vec<(Uuid, Uuid)> = get_asked_questions(state.clone(), email)
+        .await?
+        .into_iter()
+        .map(|(item_id, question_id, _)| (item_id, question_id))
+        .collect();
+
+    for _ in &question_batch_values {
+        question_batch.append_statement(state.database_queries.delete_question.clone());
+    }
+
+    if !question_batch_values.is_empty() {
+        state
+            .database_session
+            .batch(&question_batch, &question_batch_values)
+            .await?;
+    }
+
+    let mut batch: Batch = Default::default();
+    let mut batch_values: BatchRows = Vec::new();
+
+    for draft in get_drafts(state.clone(), email).await? {
+        if let Some(publish_at) = draft.publish_at {
+            batch.append_statement(state.database_queries.delete_scheduled_draft.clone());
+            batch_values.push(Box::new((
+                get_publish_bucket(publish_at),
+                publish_at,
+                email.to_string(),
+                draft.draft_id,
+            )));
+        }
+    }
+
+    for statement in [
+        &state.database_queries.delete_user,
+        &state.database_queries.delete_drafts,
+        &state.database_queries.delete_passkeys,
+    ] {
+        batch.append_statement(statement.clone());
+        batch_values.push(Box::new((email.to_string(),)));
+    }
+
+    state.database_session.batch(&batch, &batch_values).await?;
+
+    replace_recovery_codes(state.clone(), email, &[]).await
+}
+
+// Tables created before a column was introduced need it added in place, and
+// another replica booting at the same time may have already added it.
+async fn add_column(
+    database_session: &Session,
+    table: &str,
+    column: &str,
+    column_type: &str,
+) -> Result<(), AppError> {
+    if let Err(e) = database_session
+        .query_unpaged(
+            format!(
+                "ALTER TABLE {}.{} ADD {} {}",
+                KEYSPACE, table, column, column_type
+            ),
+            &[],
+        )
+        .await
+    {
+        let (returned_rows, _) = database_session
+            .query_single_page(
+                "SELECT column_name FROM system_schema.columns WHERE keyspace_name = ? AND table_name = ? AND column_name = ?",
+                (KEYSPACE, table, column),
+                PagingState::start(),
+            )
+            .await?;
+
+        if returned_rows.into_rows_result()?.rows_num() == 0 {
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
+async fn get_asked_questions(
+    state: Arc<AppState>,
+    email: &str,
+) -> Result<Vec<(Uuid, Uuid, Option<i32>)>, AppError> {
+    let mut paging_state = PagingState::start();
+
+    let mut asked_questions = Vec::new();
+
+    loop {
+        let (query_result, paging_state_response) = state
+            .database_session
+            .execute_single_page(
+                &state.database_queries.get_asked_questions,
+                (email,),
+                paging_state,
+            )
+            .await?;
+
+        for row in query_result
+            .into_rows_result()?
+            .rows::<(Uuid, Uuid, Option<i32>)>()?
+        {
+            asked_questions.push(row?);
+        }
+
+        match paging_state_response.into_paging_control_flow() {
+            ControlFlow::Break(()) => {
+                break;
+            }
+            ControlFlow::Continue(new_paging_state) => paging_state = new_paging_state,
+        }
+    }
+
+    Ok(asked_questions)
+}
+
+pub async fn insert_audit_event(
+    state: Arc<AppState>,
+    email: &str,
+    event: AuditEvent,
+) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.insert_audit_event,
+            (
+                hash_code(&state.config.audit_key, email),
+                Uuid::new_v4(),
+                event.as_ref(),
+                Utc::now(),
+            ),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn check_lock(state: Arc<AppState>, email: &str) -> Result<Option<bool>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.check_lock,
+            (email,),
+            fallback_page_state,
+        )
+        .await?;
+
+    match returned_rows.into_rows_result()?.first_row::<(bool,)>() {
+        Ok((locked,)) => Ok(Some(locked)),
+        Err(RowsEmpty) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn update_lock(state: Arc<AppState>, email: &str, lock: bool) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.update_lock,
+            (lock, email),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn unlock_account(
+    state: Arc<AppState>,
+    email: &str,
+    password_hash: &str,
+) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.unlock_account,
+            (password_hash, email),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_password(
+    state: Arc<AppState>,
+    email: &str,
+    password_hash: &str,
+) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.update_password,
+            (password_hash, email),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_totp_secret(
+    state: Arc<AppState>,
+    email: &str,
+) -> Result<Option<String>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_totp_secret,
+            (email,),
+            fallback_page_state,
+        )
+        .await?;
+
+    match returned_rows
+        .into_rows_result()?
+        .first_row::<(Option<String>,)>()
+    {
+        Ok((totp_secret,)) => Ok(totp_secret),
+        Err(RowsEmpty) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn update_totp_secret(
+    state: Arc<AppState>,
+    email: &str,
+    totp_secret: Option<String>,
+) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.update_totp_secret,
+            (totp_secret, email),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_mute_notices(state: Arc<AppState>, email: &str) -> Result<bool, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_mute_notices,
+            (email,),
+            fallback_page_state,
+        )
+        .await?;
+
+    match returned_rows
+        .into_rows_result()?
+        .first_row::<(Option<bool>,)>()
+    {
+        Ok((mute_notices,)) => Ok(mute_notices.unwrap_or(false)),
+        Err(RowsEmpty) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn update_mute_notices(
+    state: Arc<AppState>,
+    email: &str,
+    mute_notices: bool,
+) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.update_mute_notices,
+            (mute_notices, email),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_recovery_codes(
+    state: Arc<AppState>,
+    email: &str,
+) -> Result<Vec<String>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_recovery_codes,
+            (email,),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(returned_rows
+        .into_rows_result()?
+        .rows::<(String,)>()?
+        .filter_map(Result::ok)
+        .map(|(code_hash,)| code_hash)
+        .collect())
+}
+
+// Every write to the recovery codes partition is a lightweight transaction so
+// that consuming a code can never race a regular write to the same partition.
+pub async fn replace_recovery_codes(
+    state: Arc<AppState>,
+    email: &str,
+    code_hashes: &[String],
+) -> Result<(), AppError> {
+    for _ in 0..RECOVERY_CODE_WRITE_ATTEMPTS {
+        let existing = get_recovery_codes(state.clone(), email).await?;
+
+        if existing.is_empty() && code_hashes.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch: Batch = Default::default();
+        let mut batch_values = Vec::new();
+
+        for code_hash in existing {
+            batch.append_statement(state.database_queries.delete_recovery_code.clone());
+            batch_values.push((email.to_string(), code_hash));
+        }
+
+        for code_hash in code_hashes {
+            batch.append_statement(state.database_queries.insert_recovery_code.clone());
+            batch_values.push((email.to_string(), code_hash.clone()));
+        }
+
+        let returned_rows = state.database_session.batch(&batch, &batch_values).await?;
+
+        if is_applied(returned_rows)? {
+            return Ok(());
+        }
+    }
+
+    Err(AppError::Config(
+        "Recovery codes changed during replacement".into(),
+    ))
+}
+
+pub async fn remove_recovery_code(
+    state: Arc<AppState>,
+    email: &str,
+    code_hash: &str,
+) -> Result<bool, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.delete_recovery_code,
+            (email, code_hash),
+            fallback_page_state,
+        )
+        .await?;
+
+    is_applied(returned_rows)
+}
+
+fn is_applied(returned_rows: QueryResult) -> Result<bool, AppError> {
+    Ok(matches!(
+        returned_rows
+            .into_rows_result()?
+            .first_row::<Row>()?
+            .columns
+            .first(),
+        Some(Some(CqlValue::Boolean(true)))
+    ))
+}
+
+pub fn get_expiration_days(state: Arc<AppState>, item_type: &ItemType) -> i64 {
+    match item_type {
+        ItemType::Found => state.config.found_item_duration_days.into(),
+        _ => 7,
+    }
+}
+
+pub async fn insert_item(state: Arc<AppState>, item: ItemPayload) -> Result<Uuid, AppError> {
+    let fallback_page_state = PagingState::start();
+    let id = Uuid::new_v4();
+    let expiration_days = get_expiration_days(state.clone(), &item.item_type);
+    let author = item
+        .isbn
+        .as_deref()
+        .and_then(get_textbook)
+        .map(|textbook| textbook.author.clone());
+
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.insert_item,
+            (
+                &id,
+                item.item_type as i8,
+                item.title,
+                item.condition as i8,
+                item.location as i8,
+                item.description,
+                item.emoji as i8,
+                Utc::now().date_naive() + chronoDuration::days(expiration_days),
+                item.isbn,
+                author,
+                item.course_code,
+                (expiration_days * 86400 * 3) as i32,
+            ),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(id)
+}
+
+pub async fn get_item(state: Arc<AppState>, item_id: Uuid) -> Result<Option<Item>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_item,
+            (item_id,),
+            fallback_page_state,
+        )
+        .await?;
+
+    let row_result = returned_rows.into_rows_result()?;
+
+    let row_vec: Vec<ItemRow> = row_result
+        .rows::<ItemRow>()?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(convert_db_items(&row_vec).pop())
+}
+
+pub fn convert_db_items(row_vec: &Vec<ItemRow>) -> Vec<Item> {
+    row_vec
+        .iter()
+        .map(
+            |(
+                id,
+                item_type_i8,
+                title,
+                condition_i8,
+                location_i8,
+                description,
+                emoji_i8,
+                expiration_date,
+                isbn,
+                author,
+                course_code,
+            )| Item {
+                item_id: *id,
+                item_type: ItemType::try_from(convert_i8_to_u8(item_type_i8))
+                    .unwrap_or(ItemType::Other)
+                    .as_ref()
+                    .to_string(),
+                title: title.to_string(),
+                condition: Condition::try_from(convert_i8_to_u8(condition_i8))
+                    .unwrap_or(Condition::Fair)
+                    .as_ref()
+                    .to_string(),
+                location: Location::try_from(convert_i8_to_u8(location_i8))
+                    .unwrap_or(Location::CaryQuadEast)
+                    .as_ref()
+                    .to_string(),
+                description: description.to_string(),
+                emoji: Emoji::try_from(convert_i8_to_u8(emoji_i8))
+                    .unwrap_or(Emoji::Books)
+                    .as_ref()
+                    .to_string(),
+                expiration_date: expiration_date.format("%Y-%m-%d").to_string(),
+                isbn: isbn.map(|isbn| isbn.to_string()),
+                author: author.map(|author| author.to_string()),
+                course_code: course_code.map(|course_code| course_code.to_string()),
+            },
+        )
+        .collect()
+}
+
+pub async fn insert_draft(
+    state: Arc<AppState>,
+    email: &str,
+    draft: DraftPayload,
+) -> Result<Uuid, AppError> {
+    let id = Uuid::new_v4();
+
+    write_draft(
+        state.clone(),
+        email,
+        id,
+        draft.item,
+        draft.publish_at,
+        false,
+    )
+    .await?;
+
+    Ok(id)
+}
+
+pub fn get_publish_bucket(publish_at: DateTime<Utc>) -> i64 {
+    publish_at
+        .timestamp()
+        .div_euclid(scheduled_drafts::BUCKET_SECONDS)
+}
+
+// Scheduled rows expire with the draft, so nothing older than this can still
+// be waiting to publish.
+pub fn get_oldest_publish_bucket(now: DateTime<Utc>) -> Result<i64, AppError> {
+    Ok(get_publish_bucket(
+        now - chronoDuration::seconds(scheduled_drafts::TTL.parse()?),
+    ))
+}
+
+type BatchRows = Vec<Box<dyn SerializeRow + Send + Sync>>;
+
+fn append_draft(
+    state: &AppState,
+    batch: &mut Batch,
+    batch_values: &mut BatchRows,
+    email: &str,
+    draft: Draft,
+) {
+    let Draft {
+        draft_id,
+        item,
+        publish_at,
+        blocked,
+    } = draft;
+
+    batch.append_statement(state.database_queries.insert_draft.clone());
+    batch_values.push(Box::new((
+        email.to_string(),
+        draft_id,
+        item.item_type as i8,
+        item.title,
+        item.condition as i8,
+        item.location as i8,
+        item.description,
+        item.emoji as i8,
+        publish_at,
+        item.isbn,
+        item.course_code,
+        blocked,
+    )));
+
+    if let Some(publish_at) = publish_at {
+        batch.append_statement(state.database_queries.insert_scheduled_draft.clone());
+        batch_values.push(Box::new((
+            get_publish_bucket(publish_at),
+            publish_at,
+            email.to_string(),
+            draft_id,
+        )));
+    }
+}
+
+pub async fn write_draft(
+    state: Arc<AppState>,
+    email: &str,
+    draft_id: Uuid,
+    item: ItemPayload,
+    publish_at: Option<DateTime<Utc>>,
+    blocked: bool,
+) -> Result<(), AppError> {
+    let mut batch: Batch = Default::default();
+    let mut batch_values: BatchRows = Vec::new();
+
+    append_draft(
+        &state,
+        &mut batch,
+        &mut batch_values,
+        email,
+        Draft {
+            draft_id,
+            item,
+            publish_at,
+            blocked,
+        },
+    );
+
+    state.database_session.batch(&batch, &batch_values).await?;
+
+    Ok(())
+}
+
+pub async fn block_draft(state: Arc<AppState>, email: &str, draft: Draft) -> Result<(), AppError> {
+    write_draft(state.clone(), email, draft.draft_id, draft.item, None, true).await
+}
+
+pub async fn get_drafts(state: Arc<AppState>, email: &str) -> Result<Vec<Draft>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_drafts,
+            (email,),
+            fallback_page_state,
+        )
+        .await?;
+
+    let row_result = returned_rows.into_rows_result()?;
+
+    let row_vec: Vec<DraftRow> = row_result
+        .rows::<DraftRow>()?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(convert_db_drafts(&row_vec))
+}
+
+pub async fn get_draft(
+    state: Arc<AppState>,
+    email: &str,
+    draft_id: Uuid,
+) -> Result<Option<Draft>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_draft,
+            (email, draft_id),
+            fallback_page_state,
+        )
+        .await?;
+
+    match returned_rows.into_rows_result()?.first_row::<DraftRow>() {
+        Ok(row) => Ok(convert_db_drafts(&[row]).pop()),
+        Err(RowsEmpty) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
 }
 
-pub async fn get_user(
+pub async fn delete_draft(
     state: Arc<AppState>,
     email: &str,
-) -> Result<Option<(String, bool)>, AppError> {
+    draft_id: Uuid,
+) -> Result<(), AppError> {
     let fallback_page_state = PagingState::start();
-    let (returned_rows, _) = state
+    state
         .database_session
         .execute_single_page(
-            &state.database_queries.get_user,
-            (email,),
+            &state.database_queries.delete_draft,
+            (email, draft_id),
             fallback_page_state,
         )
         .await?;
 
-    match returned_rows
-        .into_rows_result()?
-        .first_row::<(String, bool)>()
-    {
-        Ok((password_hash, locked)) => Ok(Some((password_hash, locked))),
-        Err(RowsEmpty) => Ok(None),
-        Err(e) => Err(e.into()),
-    }
+    Ok(())
 }
 
-pub async fn insert_user(state: Arc<AppState>, account: RedisAccount) -> Result<(), AppError> {
+pub fn convert_db_drafts(row_vec: &[DraftRow]) -> Vec<Draft> {
+    row_vec
+        .iter()
+        .map(
+            |(
+                id,
+                item_type_i8,
+                title,
+                condition_i8,
+                location_i8,
+                description,
+                emoji_i8,
+                publish_at,
+                isbn,
+                course_code,
+                blocked,
+            )| Draft {
+                draft_id: *id,
+                item: ItemPayload {
+                    item_type: ItemType::try_from(convert_i8_to_u8(item_type_i8))
+                        .unwrap_or(ItemType::Other),
+                    condition: Condition::try_from(convert_i8_to_u8(condition_i8))
+                        .unwrap_or(Condition::Fair),
+                    title: title.to_string(),
+                    description: description.to_string(),
+                    location: Location::try_from(convert_i8_to_u8(location_i8))
+                        .unwrap_or(Location::CaryQuadEast),
+                    emoji: Emoji::try_from(convert_i8_to_u8(emoji_i8)).unwrap_or(Emoji::Books),
+                    isbn: isbn.map(|isbn| isbn.to_string()),
+                    course_code: course_code.map(|course_code| course_code.to_string()),
+                },
+                publish_at: *publish_at,
+                blocked: blocked.unwrap_or(false),
+            },
+        )
+        .collect()
+}
+
+pub async fn insert_question(
+    state: Arc<AppState>,
+    email: &str,
+    question: QuestionPayload,
+) -> Result<(), AppError> {
     let fallback_page_state = PagingState::start();
+
     state
         .database_session
         .execute_single_page(
-            &state.database_queries.insert_user,
-            (account.email, account.password_hash, false),
+            &state.database_queries.insert_question,
+            (
+                question.item_id,
+                Uuid::new_v4(),
+                email,
+                question.question,
+                Utc::now(),
+            ),
             fallback_page_state,
         )
         .await?;
@@ -269,31 +1826,63 @@ pub async fn insert_user(state: Arc<AppState>, account: RedisAccount) -> Result<
     Ok(())
 }
 
-pub async fn check_lock(state: Arc<AppState>, email: &str) -> Result<Option<bool>, AppError> {
+pub async fn get_questions(state: Arc<AppState>, item_id: Uuid) -> Result<Vec<Question>, AppError> {
     let fallback_page_state = PagingState::start();
     let (returned_rows, _) = state
         .database_session
         .execute_single_page(
-            &state.database_queries.check_lock,
-            (email,),
+            &state.database_queries.get_questions,
+            (item_id,),
             fallback_page_state,
         )
         .await?;
 
-    match returned_rows.into_rows_result()?.first_row::<(bool,)>() {
-        Ok((locked,)) => Ok(Some(locked)),
+    let row_result = returned_rows.into_rows_result()?;
+
+    let row_vec: Vec<QuestionRow> = row_result
+        .rows::<QuestionRow>()?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(row_vec
+        .iter()
+        .map(|(question_id, question, answer, asked_at)| Question {
+            question_id: *question_id,
+            question: question.to_string(),
+            answer: answer.map(|answer| answer.to_string()),
+            asked_at: *asked_at,
+        })
+        .collect())
+}
+
+pub async fn check_question(
+    state: Arc<AppState>,
+    item_id: Uuid,
+    question_id: Uuid,
+) -> Result<Option<String>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.check_question,
+            (item_id, question_id),
+            fallback_page_state,
+        )
+        .await?;
+
+    match returned_rows.into_rows_result()?.first_row::<(String,)>() {
+        Ok((email,)) => Ok(Some(email)),
         Err(RowsEmpty) => Ok(None),
         Err(e) => Err(e.into()),
     }
 }
 
-pub async fn update_lock(state: Arc<AppState>, email: &str, lock: bool) -> Result<(), AppError> {
+pub async fn answer_question(state: Arc<AppState>, answer: AnswerPayload) -> Result<(), AppError> {
     let fallback_page_state = PagingState::start();
     state
         .database_session
         .execute_single_page(
-            &state.database_queries.update_lock,
-            (lock, email),
+            &state.database_queries.answer_question,
+            (answer.answer, answer.item_id, answer.question_id),
             fallback_page_state,
         )
         .await?;
@@ -301,17 +1890,39 @@ pub async fn update_lock(state: Arc<AppState>, email: &str, lock: bool) -> Resul
     Ok(())
 }
 
-pub async fn unlock_account(
+pub async fn delete_questions(state: Arc<AppState>, item_id: Uuid) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.delete_questions,
+            (item_id,),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn insert_found_item(
     state: Arc<AppState>,
-    email: &str,
-    password_hash: &str,
+    item_id: Uuid,
+    questions: Vec<String>,
+    answer_hashes: Vec<String>,
 ) -> Result<(), AppError> {
     let fallback_page_state = PagingState::start();
+    let expiration_days = get_expiration_days(state.clone(), &ItemType::Found);
+
     state
         .database_session
         .execute_single_page(
-            &state.database_queries.unlock_account,
-            (password_hash, email),
+            &state.database_queries.insert_found_item,
+            (
+                item_id,
+                questions,
+                answer_hashes,
+                (expiration_days * 86400 * 3) as i32,
+            ),
             fallback_page_state,
         )
         .await?;
@@ -319,68 +1930,97 @@ pub async fn unlock_account(
     Ok(())
 }
 
-pub async fn insert_item(state: Arc<AppState>, item: ItemPayload) -> Result<Uuid, AppError> {
+pub async fn get_found_item(
+    state: Arc<AppState>,
+    item_id: Uuid,
+) -> Result<Option<(Vec<String>, Vec<String>)>, AppError> {
     let fallback_page_state = PagingState::start();
-    let id = Uuid::new_v4();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_found_item,
+            (item_id,),
+            fallback_page_state,
+        )
+        .await?;
+
+    match returned_rows
+        .into_rows_result()?
+        .first_row::<(Vec<String>, Vec<String>)>()
+    {
+        Ok((questions, answer_hashes)) => Ok(Some((questions, answer_hashes))),
+        Err(RowsEmpty) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
 
+pub async fn delete_found_item(state: Arc<AppState>, item_id: Uuid) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
     state
         .database_session
         .execute_single_page(
-            &state.database_queries.insert_item,
+            &state.database_queries.delete_found_item,
+            (item_id,),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn insert_passkey(
+    state: Arc<AppState>,
+    email: &str,
+    passkey: &Passkey,
+) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.insert_passkey,
             (
-                &id,
-                item.item_type as i8,
-                item.title,
-                item.condition as i8,
-                item.location as i8,
-                item.description,
-                item.emoji as i8,
-                Utc::now().date_naive() + chronoDuration::days(7),
-                604800 * 3,
+                email,
+                get_credential_id(passkey.cred_id()),
+                serde_json::to_string(passkey)?,
+                Utc::now(),
             ),
             fallback_page_state,
         )
         .await?;
 
-    Ok(id)
+    Ok(())
 }
 
-pub fn convert_db_items(row_vec: &Vec<ItemRow>) -> Vec<Item> {
-    row_vec
-        .iter()
-        .map(
-            |(
-                id,
-                item_type_i8,
-                title,
-                condition_i8,
-                location_i8,
-                description,
-                emoji_i8,
-                expiration_date,
-            )| Item {
-                item_id: *id,
-                item_type: ItemType::try_from(convert_i8_to_u8(item_type_i8))
-                    .unwrap_or(ItemType::Other)
-                    .as_ref()
-                    .to_string(),
-                title: title.to_string(),
-                condition: Condition::try_from(convert_i8_to_u8(condition_i8))
-                    .unwrap_or(Condition::Fair)
-                    .as_ref()
-                    .to_string(),
-                location: Location::try_from(convert_i8_to_u8(location_i8))
-                    .unwrap_or(Location::CaryQuadEast)
-                    .as_ref()
-                    .to_string(),
-                description: description.to_string(),
-                emoji: Emoji::try_from(convert_i8_to_u8(emoji_i8))
-                    .unwrap_or(Emoji::Books)
-                    .as_ref()
-                    .to_string(),
-                expiration_date: expiration_date.format("%Y-%m-%d").to_string(),
-            },
+pub async fn get_passkeys(state: Arc<AppState>, email: &str) -> Result<Vec<Passkey>, AppError> {
+    Ok(get_passkey_rows(state, email)
+        .await?
+        .into_iter()
+        .map(|(passkey, _)| passkey)
+        .collect())
+}
+
+async fn get_passkey_rows(
+    state: Arc<AppState>,
+    email: &str,
+) -> Result<Vec<(Passkey, Option<DateTime<Utc>>)>, AppError> {
+    let fallback_page_state = PagingState::start();
+    let (returned_rows, _) = state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.get_passkeys,
+            (email,),
+            fallback_page_state,
         )
+        .await?;
+
+    let row_result = returned_rows.into_rows_result()?;
+
+    row_result
+        .rows::<(String, Option<DateTime<Utc>>)>()?
+        .map(|row| {
+            let (passkey, created_at) = row?;
+            Ok((serde_json::from_str(&passkey)?, created_at))
+        })
         .collect()
 }
 
@@ -450,6 +2090,13 @@ pub fn get_cdc_text(data: &CDCRow<'_>, column: &str) -> String {
         .to_string()
 }
 
+pub fn get_cdc_optional_text(data: &CDCRow<'_>, column: &str) -> Option<String> {
+    data.get_value(column)
+        .as_ref()
+        .and_then(|v| v.as_text())
+        .map(|v| v.to_string())
+}
+
 pub fn get_cdc_date(data: &CDCRow<'_>, column: &str) -> String {
     let days = data
         .get_value(column)
@@ -486,24 +2133,22 @@ pub fn convert_cdc_item(data: CDCRow<'_>) -> Item {
             .as_ref()
             .to_string(),
         expiration_date: get_cdc_date(&data, items::EXPIRATION_DATE),
+        isbn: get_cdc_optional_text(&data, items::ISBN),
+        author: get_cdc_optional_text(&data, items::AUTHOR),
+        course_code: get_cdc_optional_text(&data, items::COURSE_CODE),
     }
 }
 
-pub async fn spawn_ttl_task(
-    database_session: Arc<Session>,
-    database_queries: &DatabaseQueries,
-) -> Result<(), AppError> {
+pub async fn spawn_ttl_task(state: Arc<AppState>) -> Result<(), AppError> {
     let scheduler = JobScheduler::new().await?;
 
-    let database_session = database_session.clone();
-    let database_queries = database_queries.clone();
+    let ttl_state = state.clone();
 
     scheduler
         .add(Job::new_async("1 0 0 * * *", move |_uuid, _lock| {
-            let database_session = database_session.clone();
-            let database_queries = database_queries.clone();
+            let state = ttl_state.clone();
             Box::pin(async move {
-                if expire_ttl(database_session, &database_queries)
+                if expire_ttl(state.database_session.clone(), &state.database_queries)
                     .await
                     .is_err()
                 {
@@ -513,6 +2158,19 @@ pub async fn spawn_ttl_task(
         })?)
         .await?;
 
+    let drafts_state = state.clone();
+
+    scheduler
+        .add(Job::new_async("0 * * * * *", move |_uuid, _lock| {
+            let state = drafts_state.clone();
+            Box::pin(async move {
+                if publish_scheduled_drafts(state).await.is_err() {
+                    warn!("Publishing scheduled drafts failed!");
+                }
+            })
+        })?)
+        .await?;
+
     tokio::spawn(async move {
         if scheduler.start().await.is_err() {
             warn!("Scheduler failed!");
@@ -574,3 +2232,138 @@ pub async fn expire_ttl(
 
     Ok(())
 }
+
+pub async fn get_due_drafts(
+    state: Arc<AppState>,
+    bucket: i64,
+    now: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, String, Uuid)>, AppError> {
+    let mut paging_state = PagingState::start();
+
+    let mut due_drafts = Vec::new();
+
+    loop {
+        let (query_result, paging_state_response) = state
+            .database_session
+            .execute_single_page(
+                &state.database_queries.get_scheduled_drafts,
+                (bucket, now),
+                paging_state,
+            )
+            .await?;
+
+        let row_result = query_result.into_rows_result()?;
+
+        for row in row_result.rows::<ScheduledDraftRow>()? {
+            let (publish_at, email, draft_id) = row?;
+
+            due_drafts.push((publish_at, email.to_string(), draft_id));
+        }
+
+        match paging_state_response.into_paging_control_flow() {
+            ControlFlow::Break(()) => {
+                break;
+            }
+            ControlFlow::Continue(new_paging_state) => paging_state = new_paging_state,
+        }
+    }
+
+    Ok(due_drafts)
+}
+
+pub async fn delete_scheduled_draft(
+    state: Arc<AppState>,
+    publish_at: DateTime<Utc>,
+    email: &str,
+    draft_id: Uuid,
+) -> Result<(), AppError> {
+    let fallback_page_state = PagingState::start();
+    state
+        .database_session
+        .execute_single_page(
+            &state.database_queries.delete_scheduled_draft,
+            (get_publish_bucket(publish_at), publish_at, email, draft_id),
+            fallback_page_state,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn publish_scheduled_drafts(state: Arc<AppState>) -> Result<(), AppError> {
+    let now = Utc::now();
+    let current_bucket = get_publish_bucket(now);
+
+    let oldest_bucket = get_oldest_publish_bucket(now)?;
+
+    // Resume from the oldest bucket that still had a pending draft, so an
+    // outage of any length is caught up instead of only the recent buckets.
+    let first_bucket = match get_publish_cursor(state.clone()).await? {
+        Some(cursor) => cursor.clamp(oldest_bucket, current_bucket),
+        None => oldest_bucket,
+    };
+
+    let mut due_drafts = Vec::new();
+
+    for bucket in first_bucket..=current_bucket {
+        due_drafts.extend(get_due_drafts(state.clone(), bucket, now).await?);
+    }
+
+    let mut pending_bucket = current_bucket;
+
+    for (publish_at, email, draft_id) in due_drafts {
+        let draft = match get_draft(state.clone(), &email, draft_id).await? {
+            Some(draft) if !draft.blocked && draft.publish_at == Some(publish_at) => draft,
+            _ => {
+                delete_scheduled_draft(state.clone(), publish_at, &email, draft_id).await?;
+                continue;
+            }
+        };
+
+        if is_duplicate_item(state.clone(), &email, &draft.item).await? {
+            debug!("Scheduled draft {} blocked as duplicate", draft_id);
+
+            let title = draft.item.title.clone();
+
+            block_draft(state.clone(), &email, draft).await?;
+            delete_scheduled_draft(state.clone(), publish_at, &email, draft_id).await?;
+
+            spawn_email_task(
+                state.clone(),
+                email,
+                "BoilerSwap Draft Not Published".to_string(),
+                format!(
+                    "Your scheduled listing \"{}\" was not published because it is too similar to one of your active listings. It has been kept as a draft.",
+                    title
+                ),
+            );
+            continue;
+        }
+
+        if handle_draft_publication(state.clone(), &email, draft_id).await? {
+            delete_scheduled_draft(state.clone(), publish_at, &email, draft_id).await?;
+        } else {
+            debug!("Scheduled draft {} postponed", draft_id);
+            pending_bucket = pending_bucket.min(get_publish_bucket(publish_at));
+        }
+    }
+
+    set_publish_cursor(state.clone(), pending_bucket).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catch_up_covers_every_bucket_a_scheduled_row_can_live_in() {
+        let now = Utc::now();
+        let ttl_seconds: i64 = scheduled_drafts::TTL.parse().unwrap();
+        let buckets = get_publish_bucket(now) - get_oldest_publish_bucket(now).unwrap();
+
+        assert!(buckets >= ttl_seconds / scheduled_drafts::BUCKET_SECONDS);
+        assert!(buckets > 24);
+    }
+}
@@ -0,0 +1,114 @@
+use super::{
+    database::{delete_draft, get_draft, get_drafts, insert_draft},
+    models::{DraftPayload, ItemType, RedisAction, Token},
+    redis::{handle_draft_publication, is_redis_locked},
+    schema::columns::drafts,
+    similarity::is_duplicate_item,
+    textbooks::enrich_textbook,
+    verify::{SessionUser, validate_item},
+};
+use crate::{AppError, AppState};
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use chrono::{Duration as chronoDuration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub async fn post_draft_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(mut payload): Json<DraftPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Err(e) = enrich_textbook(&mut payload.item) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    if let Err(e) = validate_item(&payload.item.title, &payload.item.description) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    if matches!(payload.item.item_type, ItemType::Found) {
+        return Ok((StatusCode::BAD_REQUEST, "Found items need verification").into_response());
+    }
+
+    if let Some(publish_at) = payload.publish_at
+        && (publish_at <= Utc::now()
+            || publish_at > Utc::now() + chronoDuration::seconds(drafts::TTL.parse()?))
+    {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid publish time").into_response());
+    }
+
+    if get_drafts(state.clone(), &email).await?.len() >= state.config.max_items.into() {
+        return Ok((StatusCode::UNAUTHORIZED, "Saved too many drafts").into_response());
+    }
+
+    Ok((
+        StatusCode::OK,
+        insert_draft(state.clone(), &email, payload)
+            .await?
+            .to_string(),
+    )
+        .into_response())
+}
+
+pub async fn drafts_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((
+        StatusCode::OK,
+        Json(get_drafts(state.clone(), &email).await?),
+    )
+        .into_response())
+}
+
+pub async fn publish_draft_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<Token>,
+) -> Result<impl IntoResponse, AppError> {
+    let draft_id = match Uuid::parse_str(&payload.token) {
+        Ok(draft_id) => draft_id,
+        Err(_) => return Ok((StatusCode::BAD_REQUEST, "Invalid draft").into_response()),
+    };
+
+    if is_redis_locked(
+        state.clone(),
+        RedisAction::LockedItems.as_ref(),
+        &email,
+        &state.config.max_items,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Posted too many items").into_response());
+    }
+
+    let draft = match get_draft(state.clone(), &email, draft_id).await? {
+        Some(draft) => draft,
+        None => return Ok((StatusCode::BAD_REQUEST, "Invalid draft").into_response()),
+    };
+
+    if is_duplicate_item(state.clone(), &email, &draft.item).await? {
+        return Ok((StatusCode::CONFLICT, "Similar item already posted").into_response());
+    }
+
+    if !handle_draft_publication(state.clone(), &email, draft_id).await? {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid draft").into_response());
+    }
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn delete_draft_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<Token>,
+) -> Result<impl IntoResponse, AppError> {
+    let draft_id = match Uuid::parse_str(&payload.token) {
+        Ok(draft_id) => draft_id,
+        Err(_) => return Ok((StatusCode::BAD_REQUEST, "Invalid draft").into_response()),
+    };
+
+    delete_draft(state.clone(), &email, draft_id).await?;
+
+    Ok((StatusCode::OK).into_response())
+}
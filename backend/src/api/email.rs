@@ -0,0 +1,55 @@
+use crate::{AppError, AppState};
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    transport::smtp::authentication::Credentials,
+};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+pub async fn send_email(
+    state: Arc<AppState>,
+    user_email: &str,
+    subject: &str,
+    body: String,
+) -> Result<(), AppError> {
+    let email = Message::builder()
+        .from(format!("BoilerSwap <{}>", state.config.from_email).parse()?)
+        .to(user_email.parse()?)
+        .subject(subject)
+        .body(body)?;
+
+    let credentials = Credentials::new(
+        state.config.from_email.to_string(),
+        state.config.from_email_password.to_string(),
+    );
+
+    let mailer = AsyncSmtpTransport::<Tokio1Executor>::relay(&state.config.from_email_server)?
+        .credentials(credentials)
+        .build();
+
+    mailer.send(email).await?;
+
+    Ok(())
+}
+
+pub fn log_email_error(error: AppError) {
+    match error {
+        AppError::LettreAddress(msg) => {
+            debug!("Invalid email: {}", msg);
+        }
+        AppError::LettreTransport(msg) => {
+            debug!("Transport error: {}", msg);
+        }
+        other => {
+            warn!("Unexpected error: {:?}", other);
+        }
+    }
+}
+
+pub fn spawn_email_task(state: Arc<AppState>, user_email: String, subject: String, body: String) {
+    tokio::spawn(async move {
+        if let Err(error) = send_email(state.clone(), &user_email, &subject, body).await {
+            log_email_error(error);
+        }
+    });
+}
@@ -0,0 +1,85 @@
+use super::{
+    database::{
+        get_drafts, get_item, get_mute_notices, get_passkeys, get_recovery_codes, get_totp_secret,
+        get_user,
+    },
+    email::{log_email_error, send_email},
+    models::{DataExport, RedisAction},
+    redis::{get_active_sessions, get_owned_item_ids, insert_id},
+};
+use crate::{AppError, AppState};
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub const EXPORT_COOLDOWN_SECONDS: i64 = 3600;
+
+pub async fn build_export(state: Arc<AppState>, email: &str) -> Result<DataExport, AppError> {
+    let locked = match get_user(state.clone(), email).await? {
+        Some((_, locked)) => locked,
+        None => return Err(AppError::Config("Export requested for missing user".into())),
+    };
+
+    let mut items = Vec::new();
+
+    for item_id in get_owned_item_ids(state.clone(), email).await? {
+        if let Some(item) = get_item(state.clone(), item_id).await? {
+            items.push(item);
+        }
+    }
+
+    Ok(DataExport {
+        email: email.to_string(),
+        locked,
+        authenticator_enabled: get_totp_secret(state.clone(), email).await?.is_some(),
+        recovery_codes_remaining: get_recovery_codes(state.clone(), email).await?.len(),
+        passkeys: get_passkeys(state.clone(), email).await?.len(),
+        mute_notices: get_mute_notices(state.clone(), email).await?,
+        items,
+        drafts: get_drafts(state.clone(), email).await?,
+        sessions: get_active_sessions(
+            state.clone(),
+            RedisAction::Session.as_ref(),
+            RedisAction::SessionStore.as_ref(),
+            email,
+            "",
+        )
+        .await?,
+        exported_at: Utc::now(),
+    })
+}
+
+pub async fn create_export(state: Arc<AppState>, email: &str) -> Result<(), AppError> {
+    let export = build_export(state.clone(), email).await?;
+    let export_id = Uuid::new_v4().to_string();
+
+    insert_id(
+        state.clone(),
+        RedisAction::DataExport.as_ref(),
+        &export_id,
+        &serde_json::to_string(&export)?,
+        state.config.export_duration_seconds,
+    )
+    .await?;
+
+    send_email(
+        state.clone(),
+        email,
+        "BoilerSwap Data Export",
+        format!(
+            "Your BoilerSwap data export is ready: {}/export/{}\n\nThis link expires in {} hours.",
+            state.config.svelte_url,
+            export_id,
+            state.config.export_duration_seconds / 3600
+        ),
+    )
+    .await
+}
+
+pub fn spawn_export_task(state: Arc<AppState>, email: String) {
+    tokio::spawn(async move {
+        if let Err(error) = create_export(state.clone(), &email).await {
+            log_email_error(error);
+        }
+    });
+}
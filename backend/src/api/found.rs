@@ -0,0 +1,159 @@
+use super::{
+    database::{get_found_item, insert_found_item},
+    email::spawn_email_task,
+    models::{ClaimPayload, Contact, FoundItemPayload, ItemType, RedisAction},
+    redis::{handle_item_insertion, increment_lock_key, is_redis_locked, remove_id, try_get},
+    similarity::{is_duplicate_item, normalize_text},
+    throttle::{ThrottleScope, get_retry_message},
+    twofactor::{hash_code, verify_code},
+    verify::{SessionUser, validate_item, validate_verification},
+};
+use crate::{AppError, state::AppState};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn get_answer(item_id: Uuid, answer: &str) -> String {
+    format!("{}:{}", item_id, normalize_text(answer))
+}
+
+pub async fn post_found_item_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<FoundItemPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Err(e) = validate_item(&payload.item.title, &payload.item.description) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    if !matches!(payload.item.item_type, ItemType::Found) {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid item type").into_response());
+    }
+
+    if let Err(e) = validate_verification(&payload.verification) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    if is_redis_locked(
+        state.clone(),
+        RedisAction::LockedItems.as_ref(),
+        &email,
+        &state.config.max_items,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Posted too many items").into_response());
+    }
+
+    if is_duplicate_item(state.clone(), &email, &payload.item).await? {
+        return Ok((StatusCode::CONFLICT, "Similar item already posted").into_response());
+    }
+
+    let item_id = handle_item_insertion(state.clone(), payload.item, &email).await?;
+
+    let (questions, answer_hashes) = payload
+        .verification
+        .iter()
+        .map(|entry| {
+            (
+                entry.question.clone(),
+                hash_code(&state.config.code_key, &get_answer(item_id, &entry.answer)),
+            )
+        })
+        .unzip();
+
+    insert_found_item(state.clone(), item_id, questions, answer_hashes).await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn found_questions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    match get_found_item(state.clone(), item_id).await? {
+        Some((questions, _)) => Ok((StatusCode::OK, Json(questions)).into_response()),
+        None => Ok((StatusCode::BAD_REQUEST, "Invalid item").into_response()),
+    }
+}
+
+pub async fn claim_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<ClaimPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    let answer_hashes = match get_found_item(state.clone(), payload.item_id).await? {
+        Some((_, answer_hashes)) => answer_hashes,
+        None => return Ok((StatusCode::BAD_REQUEST, "Invalid item").into_response()),
+    };
+
+    let finder = match try_get(
+        state.clone(),
+        RedisAction::DeletedItem.as_ref(),
+        &payload.item_id.to_string(),
+    )
+    .await?
+    {
+        Some(finder) if finder != email => finder,
+        _ => return Ok((StatusCode::BAD_REQUEST, "Invalid item").into_response()),
+    };
+
+    let claim_key = format!("{}:{}", RedisAction::LockedClaim.as_ref(), payload.item_id);
+
+    if is_redis_locked(
+        state.clone(),
+        &claim_key,
+        &email,
+        &state.config.verify_max_attempts,
+    )
+    .await?
+    {
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            get_retry_message(&state, ThrottleScope::Verify),
+        )
+            .into_response());
+    }
+
+    let answers_match = payload.answers.iter().zip(answer_hashes.iter()).fold(
+        true,
+        |matched, (answer, answer_hash)| {
+            verify_code(
+                &state.config.code_key,
+                &get_answer(payload.item_id, answer),
+                answer_hash,
+            ) & matched
+        },
+    );
+
+    if payload.answers.len() != answer_hashes.len() || !answers_match {
+        increment_lock_key(
+            state.clone(),
+            &claim_key,
+            &email,
+            &state.config.verify_lock_duration_seconds,
+            &state.config.verify_max_attempts,
+        )
+        .await?;
+        return Ok((StatusCode::UNAUTHORIZED, "Incorrect answers").into_response());
+    }
+
+    remove_id(state.clone(), &claim_key, &email).await?;
+
+    spawn_email_task(
+        state.clone(),
+        finder.clone(),
+        "BoilerSwap Claim".to_string(),
+        format!(
+            "{} answered your verification questions and received your contact email.",
+            email
+        ),
+    );
+
+    Ok((StatusCode::OK, Json(Contact { email: finder })).into_response())
+}
@@ -1,31 +1,61 @@
 use super::{
-    lock::{freeze_account, unfreeze_account},
-    models::{Account, Action, ItemPayload, RedisAccount, RedisAction, Token},
+    account::delete_account,
+    csrf::{get_bearer_token, verify_csrf},
+    database::{
+        answer_question, check_question, claim_user, get_mute_notices, get_questions,
+        get_totp_secret, get_user, insert_question, migrate_user, release_user,
+        replace_recovery_codes, update_mute_notices, update_password, update_totp_secret,
+    },
+    email::spawn_email_task,
+    export::{EXPORT_COOLDOWN_SECONDS, spawn_export_task},
+    lock::{check_locks, freeze_account, unfreeze_account},
+    magiclink::is_magic_token,
+    models::{
+        Account, Action, AnswerPayload, EmailChange, EmailChangeCodes, ItemPayload, ItemType,
+        NotificationPreferences, PasswordChange, QuestionPayload, RecoveryCodes, RedisAccount,
+        RedisAction, Token, TotpEnrollment,
+    },
+    notifications::{SecurityNotice, spawn_notice_task},
+    recovery::{is_recovery_code, store_recovery_codes},
     redis::{
-        create_redis_account, get_redis_account, handle_item_insertion, increment_lock_key,
-        is_redis_locked, remove_id,
+        create_redis_account, delete_all_sessions, get_active_sessions, get_redis_account,
+        handle_item_insertion, increment_lock_key, insert_id, is_redis_locked,
+        is_remembered_session, is_temporarily_locked, migrate_redis_account, remove_id,
+        revoke_session_by_hash, try_get, update_id,
     },
     sessions::{create_session, create_temporary_session, generate_cookie, get_cookie},
-    twofactor::{CODE_REGEX, generate_code},
-    utilities::{get_hashed_ip, get_key},
+    similarity::is_duplicate_item,
+    textbooks::enrich_textbook,
+    throttle::{
+        ThrottleScope, clear_failures, get_retry_message, is_throttled, record_failure, slow_down,
+    },
+    totp::{
+        decrypt_secret, encrypt_secret, generate_totp_secret, get_provisioning_uri, verify_totp,
+        verify_user_totp,
+    },
+    twofactor::{CODE_REGEX, generate_code, hash_code, spawn_code_task, verify_code},
+    utilities::{get_device_label, get_hash, get_hashed_ip, get_key},
     verify::{
-        CODE_LENGTH, validate_account, validate_api_token, validate_email, validate_item,
-        validate_password, verify_token,
+        CODE_LENGTH, SessionUser, spawn_hash_password, spawn_verify_password, validate_account,
+        validate_api_token, validate_bearer_token, validate_email, validate_item,
+        validate_item_attribute, validate_password, verify_token,
     },
 };
 use crate::{AppError, state::AppState};
 use axum::{
     Json,
-    extract::{ConnectInfo, Request, State},
+    extract::{ConnectInfo, Path, Request, State},
     http::{
         StatusCode,
-        header::{HeaderMap, ORIGIN},
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, HeaderMap, ORIGIN, REFERER},
     },
     middleware::Next,
     response::IntoResponse,
 };
+use chrono::Utc;
 use redis::AsyncTypedCommands;
 use std::{net::SocketAddr, sync::Arc};
+use uuid::Uuid;
 
 pub async fn api_token_check(
     headers: HeaderMap,
@@ -33,20 +63,30 @@ pub async fn api_token_check(
     request: Request,
     next: Next,
 ) -> Result<impl IntoResponse, AppError> {
-    let origin = headers.get(ORIGIN);
-
-    if origin.is_none() {
+    if get_bearer_token(&headers).is_some() {
+        if validate_bearer_token(state.clone(), &headers) {
+            return Ok(next.run(request).await);
+        }
         return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
     }
 
-    if origin.expect("is_none failed").as_bytes() != state.config.svelte_url.as_bytes() {
+    let origin = headers
+        .get(ORIGIN)
+        .or_else(|| headers.get(REFERER))
+        .and_then(|v| v.to_str().ok());
+
+    if let Some(origin) = origin
+        && origin != state.config.svelte_url
+        && !origin.starts_with(&format!("{}/", state.config.svelte_url))
+    {
         return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
     }
 
-    if validate_api_token(headers) {
-        return Ok(next.run(request).await);
+    if !validate_api_token(state.clone(), &headers) {
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
     }
-    Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response())
+
+    Ok(next.run(request).await)
 }
 
 pub async fn forgot_handler(
@@ -59,7 +99,7 @@ pub async fn forgot_handler(
         return Ok(StatusCode::UNAUTHORIZED.into_response());
     }
 
-    let hashed_ip = get_hashed_ip(&headers, address.ip());
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
 
     let forgot_key = get_key(RedisAction::LockedForgot, &hashed_ip);
     let failed_verify_key = get_key(RedisAction::LockedVerify, &hashed_ip);
@@ -89,6 +129,8 @@ pub async fn forgot_handler(
         code: generate_code().clone(),
         issued_timestamp: None,
         password_hash: None,
+        remember: false,
+        attempts: 0,
     };
 
     Ok((
@@ -112,6 +154,10 @@ pub async fn delete_handler(
 ) -> Result<impl IntoResponse, AppError> {
     let id = get_cookie(&headers, RedisAction::Session.as_ref());
 
+    if id.is_some() && !verify_csrf(&state.config.csrf_key, &headers) {
+        return Ok((StatusCode::FORBIDDEN, "Invalid CSRF Token").into_response());
+    }
+
     if id.is_some() {
         state
             .redis_connection_manager
@@ -145,23 +191,40 @@ pub async fn verify_handler(
         }
     };
 
-    if redis_action == RedisAction::Update && validate_password(&payload.token).is_err() {
-        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    if redis_action == RedisAction::Update
+        && let Err(e) = validate_password(&payload.token, None)
+    {
+        return Ok((StatusCode::BAD_REQUEST, Json(e)).into_response());
     }
 
-    if (redis_action == RedisAction::Auth || redis_action == RedisAction::Forgot)
-        && (payload.token.len() != *CODE_LENGTH || !CODE_REGEX.is_match(&payload.token))
-    {
+    if !has_valid_token_format(&redis_action, &payload.token) {
         return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
     }
 
-    let hashed_ip = get_hashed_ip(&headers, address.ip());
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
 
     let forgot_key = get_key(RedisAction::LockedForgot, &hashed_ip);
     let failed_verify_key = get_key(RedisAction::LockedVerify, &hashed_ip);
     let failed_auth_key = get_key(RedisAction::LockedAuth, &hashed_ip);
     let code_key = get_key(RedisAction::LockedCode, &hashed_ip);
 
+    let email = match &result {
+        Some(serialized) => serde_json::from_str::<RedisAccount>(serialized)?.email,
+        None => {
+            return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+        }
+    };
+
+    if is_throttled(state.clone(), ThrottleScope::Verify, &hashed_ip, &email).await? {
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            get_retry_message(&state, ThrottleScope::Verify),
+        )
+            .into_response());
+    }
+
+    slow_down(state.clone(), ThrottleScope::Verify, &hashed_ip, &email).await?;
+
     let redis_account = match get_redis_account(
         state.clone(),
         &result,
@@ -178,15 +241,24 @@ pub async fn verify_handler(
             remove_id(state.clone(), &failed_auth_key, &account.email).await?;
             remove_id(state.clone(), &forgot_key, &account.email).await?;
             remove_id(state.clone(), &code_key, &account.email).await?;
+            clear_failures(state.clone(), ThrottleScope::Verify, &account.email).await?;
             account
         }
         None => {
+            if redis_action != RedisAction::Update {
+                record_failure(state.clone(), ThrottleScope::Verify, &hashed_ip, &email).await?;
+            }
             return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
         }
     };
 
     if redis_action == RedisAction::Forgot {
-        freeze_account(state.clone(), &redis_account.email).await?;
+        freeze_account(
+            state.clone(),
+            &redis_account.email,
+            &get_device_label(&headers),
+        )
+        .await?;
 
         return Ok((
             StatusCode::OK,
@@ -205,6 +277,13 @@ pub async fn verify_handler(
 
     if redis_action == RedisAction::Update {
         unfreeze_account(state.clone(), &redis_account.email, &payload.token).await?;
+
+        spawn_notice_task(
+            state.clone(),
+            redis_account.email.clone(),
+            SecurityNotice::PasswordChanged,
+            get_device_label(&headers),
+        );
     }
 
     Ok((
@@ -214,19 +293,31 @@ pub async fn verify_handler(
             &redis_account,
             RedisAction::Session,
             RedisAction::SessionStore,
+            &headers,
+            address.ip(),
         )
         .await?,
     )
         .into_response())
 }
 
+fn has_valid_token_format(redis_action: &RedisAction, token: &str) -> bool {
+    if *redis_action != RedisAction::Auth && *redis_action != RedisAction::Forgot {
+        return true;
+    }
+
+    (token.len() == *CODE_LENGTH && CODE_REGEX.is_match(token))
+        || (*redis_action == RedisAction::Auth
+            && (is_recovery_code(token) || is_magic_token(token)))
+}
+
 pub async fn authenticate_handler(
     headers: HeaderMap,
     ConnectInfo(address): ConnectInfo<SocketAddr>,
     State(state): State<Arc<AppState>>,
     Json(payload): Json<Account>,
 ) -> Result<impl IntoResponse, AppError> {
-    let hashed_ip = get_hashed_ip(&headers, address.ip());
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
 
     let failed_auth_key = get_key(RedisAction::LockedAuth, &hashed_ip);
     let code_key = get_key(RedisAction::LockedCode, &hashed_ip);
@@ -245,11 +336,29 @@ pub async fn authenticate_handler(
             &state.config.max_codes,
         )
         .await?
+        || is_throttled(
+            state.clone(),
+            ThrottleScope::Auth,
+            &hashed_ip,
+            &payload.email,
+        )
+        .await?
     {
-        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            get_retry_message(&state, ThrottleScope::Auth),
+        )
+            .into_response());
     }
 
     if payload.action == Action::Forgot {
+        record_failure(
+            state.clone(),
+            ThrottleScope::Auth,
+            &hashed_ip,
+            &payload.email,
+        )
+        .await?;
         increment_lock_key(
             state.clone(),
             &failed_auth_key,
@@ -265,6 +374,20 @@ pub async fn authenticate_handler(
         return Ok((StatusCode::BAD_REQUEST, e).into_response());
     }
 
+    if payload.action == Action::Signup
+        && let Err(e) = validate_password(&payload.password, Some(&payload.email))
+    {
+        return Ok((StatusCode::BAD_REQUEST, Json(e)).into_response());
+    }
+
+    slow_down(
+        state.clone(),
+        ThrottleScope::Auth,
+        &hashed_ip,
+        &payload.email,
+    )
+    .await?;
+
     let redis_account = match create_redis_account(
         state.clone(),
         payload.action,
@@ -274,11 +397,20 @@ pub async fn authenticate_handler(
     )
     .await?
     {
-        Some(account) => {
+        Some(mut account) => {
             remove_id(state.clone(), &failed_auth_key, &payload.email).await?;
+            clear_failures(state.clone(), ThrottleScope::Auth, &payload.email).await?;
+            account.remember = payload.remember;
             account
         }
         None => {
+            record_failure(
+                state.clone(),
+                ThrottleScope::Auth,
+                &hashed_ip,
+                &payload.email,
+            )
+            .await?;
             return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
         }
     };
@@ -301,7 +433,7 @@ pub async fn authenticate_handler(
 pub async fn post_item_handler(
     headers: HeaderMap,
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<ItemPayload>,
+    Json(mut payload): Json<ItemPayload>,
 ) -> Result<impl IntoResponse, AppError> {
     let email = match verify_token(state.clone(), headers.clone()).await? {
         Some((a, pending_redis_action, _)) => {
@@ -315,10 +447,18 @@ pub async fn post_item_handler(
         }
     };
 
+    if let Err(e) = enrich_textbook(&mut payload) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
     if let Err(e) = validate_item(&payload.title, &payload.description) {
         return Ok((StatusCode::BAD_REQUEST, e).into_response());
     }
 
+    if matches!(payload.item_type, ItemType::Found) {
+        return Ok((StatusCode::BAD_REQUEST, "Found items need verification").into_response());
+    }
+
     if is_redis_locked(
         state.clone(),
         RedisAction::LockedItems.as_ref(),
@@ -330,6 +470,16 @@ pub async fn post_item_handler(
         return Ok((StatusCode::UNAUTHORIZED, "Posted too many items").into_response());
     }
 
+    if is_duplicate_item(
+        state.clone(),
+        email.as_ref().expect("session creation faulty"),
+        &payload,
+    )
+    .await?
+    {
+        return Ok((StatusCode::CONFLICT, "Similar item already posted").into_response());
+    }
+
     handle_item_insertion(
         state.clone(),
         payload,
@@ -340,6 +490,776 @@ pub async fn post_item_handler(
     Ok((StatusCode::OK).into_response())
 }
 
+pub async fn questions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((
+        StatusCode::OK,
+        Json(get_questions(state.clone(), item_id).await?),
+    )
+        .into_response())
+}
+
+pub async fn ask_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<QuestionPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Err(e) = validate_item_attribute(&payload.question) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    let owner = match try_get(
+        state.clone(),
+        RedisAction::DeletedItem.as_ref(),
+        &payload.item_id.to_string(),
+    )
+    .await?
+    {
+        Some(owner) => owner,
+        None => return Ok((StatusCode::BAD_REQUEST, "Invalid item").into_response()),
+    };
+
+    if owner == email {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid item").into_response());
+    }
+
+    if is_redis_locked(
+        state.clone(),
+        RedisAction::LockedQuestions.as_ref(),
+        &email,
+        &state.config.max_questions,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Asked too many questions").into_response());
+    }
+
+    increment_lock_key(
+        state.clone(),
+        RedisAction::LockedQuestions.as_ref(),
+        &email,
+        &state.config.max_questions_duration_seconds,
+        &state.config.max_questions,
+    )
+    .await?;
+
+    spawn_email_task(
+        state.clone(),
+        owner,
+        "BoilerSwap Question".to_string(),
+        format!("Someone asked about your item: {}", payload.question),
+    );
+
+    insert_question(state.clone(), &email, payload).await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn answer_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<AnswerPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Err(e) = validate_item_attribute(&payload.answer) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    if try_get(
+        state.clone(),
+        RedisAction::DeletedItem.as_ref(),
+        &payload.item_id.to_string(),
+    )
+    .await?
+    .as_deref()
+        != Some(email.as_str())
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    }
+
+    if check_question(state.clone(), payload.item_id, payload.question_id)
+        .await?
+        .is_none()
+    {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid question").into_response());
+    }
+
+    answer_question(state.clone(), payload).await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn totp_enroll_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    if get_totp_secret(state.clone(), &email).await?.is_some() {
+        return Ok((StatusCode::BAD_REQUEST, "Authenticator already enabled").into_response());
+    }
+
+    let secret = generate_totp_secret();
+
+    insert_id(
+        state.clone(),
+        RedisAction::TotpPending.as_ref(),
+        &email,
+        &encrypt_secret(&state.config.totp_key, &secret)?,
+        state.config.temporary_session_duration_seconds.into(),
+    )
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(TotpEnrollment {
+            provisioning_uri: get_provisioning_uri(&email, &secret),
+            secret,
+        }),
+    )
+        .into_response())
+}
+
+pub async fn totp_confirm_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<Token>,
+) -> Result<impl IntoResponse, AppError> {
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let failed_verify_key = get_key(RedisAction::LockedVerify, &hashed_ip);
+
+    if is_redis_locked(
+        state.clone(),
+        &failed_verify_key,
+        &email,
+        &state.config.verify_max_attempts,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    let encrypted = match try_get(state.clone(), RedisAction::TotpPending.as_ref(), &email).await? {
+        Some(encrypted) => encrypted,
+        None => return Ok((StatusCode::BAD_REQUEST, "Enrollment expired").into_response()),
+    };
+
+    let secret = decrypt_secret(&state.config.totp_key, &encrypted)?;
+
+    if !verify_totp(state.clone(), &email, &secret, &payload.token).await? {
+        increment_lock_key(
+            state.clone(),
+            &failed_verify_key,
+            &email,
+            &state.config.verify_lock_duration_seconds,
+            &state.config.verify_max_attempts,
+        )
+        .await?;
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    }
+
+    remove_id(state.clone(), &failed_verify_key, &email).await?;
+    remove_id(state.clone(), RedisAction::TotpPending.as_ref(), &email).await?;
+
+    update_totp_secret(state.clone(), &email, Some(encrypted)).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(RecoveryCodes {
+            codes: store_recovery_codes(state.clone(), &email).await?,
+        }),
+    )
+        .into_response())
+}
+
+async fn verify_totp_step_up(
+    state: Arc<AppState>,
+    hashed_ip: &str,
+    email: &str,
+    token: &str,
+) -> Result<Option<&'static str>, AppError> {
+    let failed_verify_key = get_key(RedisAction::LockedVerify, hashed_ip);
+
+    if is_redis_locked(
+        state.clone(),
+        &failed_verify_key,
+        email,
+        &state.config.verify_max_attempts,
+    )
+    .await?
+    {
+        return Ok(Some("Try again in 30 minutes"));
+    }
+
+    if !verify_user_totp(state.clone(), email, token).await? {
+        increment_lock_key(
+            state.clone(),
+            &failed_verify_key,
+            email,
+            &state.config.verify_lock_duration_seconds,
+            &state.config.verify_max_attempts,
+        )
+        .await?;
+        return Ok(Some("Invalid Credentials"));
+    }
+
+    remove_id(state.clone(), &failed_verify_key, email).await?;
+
+    Ok(None)
+}
+
+pub async fn recovery_codes_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<Token>,
+) -> Result<impl IntoResponse, AppError> {
+    if get_totp_secret(state.clone(), &email).await?.is_none() {
+        return Ok((StatusCode::BAD_REQUEST, "Authenticator not enabled").into_response());
+    }
+
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+
+    if let Some(e) = verify_totp_step_up(state.clone(), &hashed_ip, &email, &payload.token).await? {
+        return Ok((StatusCode::UNAUTHORIZED, e).into_response());
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(RecoveryCodes {
+            codes: store_recovery_codes(state.clone(), &email).await?,
+        }),
+    )
+        .into_response())
+}
+
+pub async fn totp_disable_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<Token>,
+) -> Result<impl IntoResponse, AppError> {
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+
+    if let Some(e) = verify_totp_step_up(state.clone(), &hashed_ip, &email, &payload.token).await? {
+        return Ok((StatusCode::UNAUTHORIZED, e).into_response());
+    }
+
+    update_totp_secret(state.clone(), &email, None).await?;
+
+    replace_recovery_codes(state.clone(), &email, &[]).await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn sessions_handler(
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    let current_session_id =
+        get_cookie(&headers, RedisAction::Session.as_ref()).unwrap_or_default();
+
+    Ok((
+        StatusCode::OK,
+        Json(
+            get_active_sessions(
+                state.clone(),
+                RedisAction::Session.as_ref(),
+                RedisAction::SessionStore.as_ref(),
+                &email,
+                &current_session_id,
+            )
+            .await?,
+        ),
+    )
+        .into_response())
+}
+
+pub async fn revoke_session_handler(
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Path(session_hash): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    if !revoke_session_by_hash(
+        state.clone(),
+        RedisAction::Session.as_ref(),
+        RedisAction::SessionStore.as_ref(),
+        &session_hash,
+        &email,
+    )
+    .await?
+    {
+        return Ok((StatusCode::NOT_FOUND, "Session not found").into_response());
+    }
+
+    if get_cookie(&headers, RedisAction::Session.as_ref())
+        .is_some_and(|session_id| get_hash(&session_id) == session_hash)
+    {
+        return Ok((StatusCode::OK, generate_cookie("", "", 0)).into_response());
+    }
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn revoke_all_sessions_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    delete_all_sessions(
+        state.clone(),
+        RedisAction::Session.as_ref(),
+        RedisAction::SessionStore.as_ref(),
+        &email,
+    )
+    .await?;
+
+    Ok((StatusCode::OK, generate_cookie("", "", 0)).into_response())
+}
+
+pub async fn change_password_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<PasswordChange>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Err(e) = validate_password(&payload.new_password, Some(&email)) {
+        return Ok((StatusCode::BAD_REQUEST, Json(e)).into_response());
+    }
+
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let failed_verify_key = get_key(RedisAction::LockedVerify, &hashed_ip);
+
+    if is_redis_locked(
+        state.clone(),
+        &failed_verify_key,
+        &email,
+        &state.config.verify_max_attempts,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    let password_hash = match get_user(state.clone(), &email).await? {
+        Some((password_hash, false)) => password_hash,
+        _ => {
+            return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+        }
+    };
+
+    let current_password = payload.current_password;
+
+    if !spawn_verify_password(current_password, password_hash).await? {
+        increment_lock_key(
+            state.clone(),
+            &failed_verify_key,
+            &email,
+            &state.config.verify_lock_duration_seconds,
+            &state.config.verify_max_attempts,
+        )
+        .await?;
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    }
+
+    remove_id(state.clone(), &failed_verify_key, &email).await?;
+
+    let new_password = payload.new_password;
+
+    let remember = match get_cookie(&headers, RedisAction::Session.as_ref()) {
+        Some(session_id) => is_remembered_session(state.clone(), &session_id, &email).await?,
+        None => false,
+    };
+
+    update_password(
+        state.clone(),
+        &email,
+        &spawn_hash_password(new_password).await?,
+    )
+    .await?;
+
+    delete_all_sessions(
+        state.clone(),
+        RedisAction::Session.as_ref(),
+        RedisAction::SessionStore.as_ref(),
+        &email,
+    )
+    .await?;
+
+    spawn_notice_task(
+        state.clone(),
+        email.clone(),
+        SecurityNotice::PasswordChanged,
+        get_device_label(&headers),
+    );
+
+    Ok((
+        StatusCode::OK,
+        create_session(
+            state.clone(),
+            &RedisAccount {
+                email,
+                action: Action::Login,
+                code: String::new(),
+                issued_timestamp: None,
+                password_hash: None,
+                remember,
+                attempts: 0,
+            },
+            RedisAction::Session,
+            RedisAction::SessionStore,
+            &headers,
+            address.ip(),
+        )
+        .await?,
+    )
+        .into_response())
+}
+
+pub async fn change_email_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<Token>,
+) -> Result<impl IntoResponse, AppError> {
+    let new_email = payload.token;
+
+    if let Err(e) = validate_email(&new_email) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    if new_email == email {
+        return Ok((StatusCode::BAD_REQUEST, "Email unavailable").into_response());
+    }
+
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let code_key = get_key(RedisAction::LockedCode, &hashed_ip);
+
+    if is_redis_locked(state.clone(), &code_key, &email, &state.config.max_codes).await? {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    if get_user(state.clone(), &new_email).await?.is_some() {
+        increment_lock_key(
+            state.clone(),
+            &code_key,
+            &email,
+            &state.config.max_codes_duration_seconds,
+            &state.config.max_codes,
+        )
+        .await?;
+
+        return Ok((StatusCode::OK).into_response());
+    }
+
+    let issued_timestamp = Some(Utc::now().timestamp_millis());
+
+    let mut email_change = EmailChange {
+        current: RedisAccount {
+            email: email.clone(),
+            action: Action::Login,
+            code: generate_code(),
+            issued_timestamp,
+            password_hash: None,
+            remember: false,
+            attempts: 0,
+        },
+        new: RedisAccount {
+            email: new_email,
+            action: Action::Login,
+            code: generate_code(),
+            issued_timestamp,
+            password_hash: None,
+            remember: false,
+            attempts: 0,
+        },
+    };
+
+    for redis_account in [&mut email_change.current, &mut email_change.new] {
+        spawn_code_task(
+            state.clone(),
+            redis_account.email.clone(),
+            redis_account.code.clone(),
+            None,
+            None,
+        );
+
+        redis_account.code = hash_code(&state.config.code_key, &redis_account.code);
+    }
+
+    increment_lock_key(
+        state.clone(),
+        &code_key,
+        &email,
+        &state.config.max_codes_duration_seconds,
+        &state.config.max_codes,
+    )
+    .await?;
+
+    insert_id(
+        state.clone(),
+        RedisAction::EmailChange.as_ref(),
+        &email,
+        &serde_json::to_string(&email_change)?,
+        state.config.temporary_session_duration_seconds.into(),
+    )
+    .await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn confirm_email_change_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<EmailChangeCodes>,
+) -> Result<impl IntoResponse, AppError> {
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let failed_verify_key = get_key(RedisAction::LockedVerify, &hashed_ip);
+
+    if is_redis_locked(
+        state.clone(),
+        &failed_verify_key,
+        &email,
+        &state.config.verify_max_attempts,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    let mut email_change: EmailChange =
+        match try_get(state.clone(), RedisAction::EmailChange.as_ref(), &email).await? {
+            Some(email_change) => serde_json::from_str(&email_change)?,
+            None => {
+                return Ok((StatusCode::BAD_REQUEST, "Email change expired").into_response());
+            }
+        };
+
+    let valid_code = verify_code(
+        &state.config.code_key,
+        &payload.code,
+        &email_change.current.code,
+    );
+    let valid_new_code = verify_code(
+        &state.config.code_key,
+        &payload.new_code,
+        &email_change.new.code,
+    );
+
+    if !(valid_code && valid_new_code) {
+        increment_lock_key(
+            state.clone(),
+            &failed_verify_key,
+            &email,
+            &state.config.verify_lock_duration_seconds,
+            &state.config.verify_max_attempts,
+        )
+        .await?;
+
+        email_change.current.attempts += 1;
+
+        if email_change.current.attempts >= state.config.verify_max_attempts {
+            remove_id(state.clone(), RedisAction::EmailChange.as_ref(), &email).await?;
+        } else {
+            update_id(
+                state.clone(),
+                RedisAction::EmailChange.as_ref(),
+                &email,
+                &serde_json::to_string(&email_change)?,
+            )
+            .await?;
+        }
+
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    }
+
+    remove_id(state.clone(), &failed_verify_key, &email).await?;
+    remove_id(state.clone(), RedisAction::EmailChange.as_ref(), &email).await?;
+
+    let new_email = email_change.new.email;
+
+    if match email_change.current.issued_timestamp {
+        Some(issued_timestamp) => check_locks(state.clone(), &email, issued_timestamp).await?,
+        None => true,
+    } {
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    }
+
+    if !claim_user(state.clone(), &email, &new_email).await? {
+        return Ok((StatusCode::BAD_REQUEST, "Email unavailable").into_response());
+    }
+
+    if let Err(e) = migrate_redis_account(state.clone(), &email, &new_email).await {
+        release_user(state.clone(), &new_email).await?;
+        return Err(e);
+    }
+
+    if let Err(e) = migrate_user(state.clone(), &email, &new_email).await {
+        migrate_redis_account(state.clone(), &new_email, &email).await?;
+        release_user(state.clone(), &new_email).await?;
+        return Err(e);
+    }
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn account_deletion_code_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let code_key = get_key(RedisAction::LockedCode, &hashed_ip);
+
+    if is_redis_locked(state.clone(), &code_key, &email, &state.config.max_codes).await? {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    let code = generate_code();
+
+    spawn_code_task(state.clone(), email.clone(), code.clone(), None, None);
+
+    increment_lock_key(
+        state.clone(),
+        &code_key,
+        &email,
+        &state.config.max_codes_duration_seconds,
+        &state.config.max_codes,
+    )
+    .await?;
+
+    insert_id(
+        state.clone(),
+        RedisAction::AccountDeletion.as_ref(),
+        &email,
+        &hash_code(&state.config.code_key, &code),
+        state.config.temporary_session_duration_seconds.into(),
+    )
+    .await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn delete_account_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<Token>,
+) -> Result<impl IntoResponse, AppError> {
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let failed_verify_key = get_key(RedisAction::LockedVerify, &hashed_ip);
+
+    if is_redis_locked(
+        state.clone(),
+        &failed_verify_key,
+        &email,
+        &state.config.verify_max_attempts,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    if !try_get(state.clone(), RedisAction::AccountDeletion.as_ref(), &email)
+        .await?
+        .is_some_and(|code_hash| verify_code(&state.config.code_key, &payload.token, &code_hash))
+    {
+        increment_lock_key(
+            state.clone(),
+            &failed_verify_key,
+            &email,
+            &state.config.verify_lock_duration_seconds,
+            &state.config.verify_max_attempts,
+        )
+        .await?;
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    }
+
+    delete_account(state.clone(), &email).await?;
+
+    Ok((StatusCode::OK, generate_cookie("", "", 0)).into_response())
+}
+
+pub async fn export_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    if is_temporarily_locked(
+        state.clone(),
+        RedisAction::LockedExport.as_ref(),
+        &email,
+        EXPORT_COOLDOWN_SECONDS,
+    )
+    .await?
+    {
+        return Ok((StatusCode::TOO_MANY_REQUESTS, "Try again in 1 hour").into_response());
+    }
+
+    spawn_export_task(state.clone(), email);
+
+    Ok((StatusCode::ACCEPTED).into_response())
+}
+
+pub async fn download_export_handler(
+    State(state): State<Arc<AppState>>,
+    Path(export_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    match try_get(
+        state.clone(),
+        RedisAction::DataExport.as_ref(),
+        &export_id.to_string(),
+    )
+    .await?
+    {
+        Some(export) => Ok((
+            StatusCode::OK,
+            [
+                (CONTENT_TYPE, "application/json"),
+                (
+                    CONTENT_DISPOSITION,
+                    "attachment; filename=\"boilerswap-export.json\"",
+                ),
+            ],
+            export,
+        )
+            .into_response()),
+        None => Ok((StatusCode::NOT_FOUND, "Export expired").into_response()),
+    }
+}
+
+pub async fn notifications_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((
+        StatusCode::OK,
+        Json(NotificationPreferences {
+            mute_notices: get_mute_notices(state.clone(), &email).await?,
+        }),
+    )
+        .into_response())
+}
+
+pub async fn update_notifications_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<NotificationPreferences>,
+) -> Result<impl IntoResponse, AppError> {
+    update_mute_notices(state.clone(), &email, payload.mute_notices).await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
 pub async fn resend_handler(
     headers: HeaderMap,
     ConnectInfo(address): ConnectInfo<SocketAddr>,
@@ -365,8 +1285,8 @@ pub async fn resend_handler(
 
     remove_id(state.clone(), redis_action.as_ref(), &id).await?;
 
-    let redis_account: RedisAccount = serde_json::from_str(&result.expect("is_none failed"))?;
-    let hashed_ip = get_hashed_ip(&headers, address.ip());
+    let mut redis_account: RedisAccount = serde_json::from_str(&result.expect("is_none failed"))?;
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
     let code_key = get_key(RedisAction::LockedCode, &hashed_ip);
 
     if is_redis_locked(
@@ -380,6 +1300,9 @@ pub async fn resend_handler(
         return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
     }
 
+    redis_account.code = generate_code();
+    redis_account.attempts = 0;
+
     Ok((
         StatusCode::OK,
         create_temporary_session(
@@ -394,3 +1317,37 @@ pub async fn resend_handler(
     )
         .into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{magiclink::get_magic_token, redis::matches_temporary_code};
+
+    const CODE_KEY: &str = "code-key";
+    const MAGIC_LINK_KEY: &str = "magic-link-key";
+
+    #[test]
+    fn verify_accepts_magic_token_only_for_its_auth_session() {
+        let code = "1".repeat(*CODE_LENGTH);
+        let stored_code = hash_code(CODE_KEY, &code);
+        let token = get_magic_token(MAGIC_LINK_KEY, "auth-id", &stored_code);
+
+        let accepts = |redis_action: RedisAction, id: &str, token: &str| {
+            has_valid_token_format(&redis_action, token)
+                && matches_temporary_code(
+                    CODE_KEY,
+                    MAGIC_LINK_KEY,
+                    &redis_action,
+                    id,
+                    &stored_code,
+                    token,
+                )
+        };
+
+        assert!(accepts(RedisAction::Auth, "auth-id", &token));
+        assert!(accepts(RedisAction::Auth, "auth-id", &code));
+        assert!(!accepts(RedisAction::Auth, "other-id", &token));
+        assert!(!accepts(RedisAction::Forgot, "auth-id", &token));
+        assert!(!accepts(RedisAction::Auth, "auth-id", &token[1..]));
+    }
+}
@@ -1,14 +1,14 @@
 use super::{
     database::{check_lock, unlock_account, update_lock},
     models::RedisAction,
+    notifications::{SecurityNotice, spawn_notice_task},
     redis::{delete_all_sessions, try_get},
-    verify::hash_password,
+    verify::spawn_hash_password,
 };
 use crate::{AppError, AppState};
 use chrono::{Duration as chronoDuration, Utc};
 use redis::AsyncTypedCommands;
 use std::sync::Arc;
-use tokio::task::spawn_blocking;
 
 pub async fn check_db_lock(state: Arc<AppState>, email: &str) -> Result<bool, AppError> {
     let locked = check_lock(state.clone(), email).await?;
@@ -19,7 +19,11 @@ pub async fn check_db_lock(state: Arc<AppState>, email: &str) -> Result<bool, Ap
     Ok(false)
 }
 
-pub async fn freeze_account(state: Arc<AppState>, email: &str) -> Result<(), AppError> {
+pub async fn freeze_account(
+    state: Arc<AppState>,
+    email: &str,
+    device: &str,
+) -> Result<(), AppError> {
     if check_db_lock(state.clone(), email).await? {
         return Ok(());
     }
@@ -44,6 +48,13 @@ pub async fn freeze_account(state: Arc<AppState>, email: &str) -> Result<(), App
     )
     .await?;
 
+    spawn_notice_task(
+        state.clone(),
+        email.to_string(),
+        SecurityNotice::AccountFrozen,
+        device.to_string(),
+    );
+
     Ok(())
 }
 
@@ -57,7 +68,7 @@ pub async fn unfreeze_account(
     unlock_account(
         state.clone(),
         email,
-        &spawn_blocking(move || hash_password(&password_owned)).await??,
+        &spawn_hash_password(password_owned).await?,
     )
     .await?;
 
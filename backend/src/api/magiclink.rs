@@ -0,0 +1,83 @@
+use crate::AppState;
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sha2::Sha256;
+
+pub static MAGIC_TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9a-f]{64}$").unwrap());
+
+fn get_mac(key: &str, auth_id: &str, code: &str) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_bytes())
+        .expect("hmac accepts keys of any length");
+
+    mac.update(auth_id.as_bytes());
+    mac.update(b":");
+    mac.update(code.as_bytes());
+
+    mac
+}
+
+pub fn is_magic_token(token: &str) -> bool {
+    MAGIC_TOKEN_REGEX.is_match(token)
+}
+
+pub fn get_magic_token(key: &str, auth_id: &str, code: &str) -> String {
+    HEXLOWER.encode(&get_mac(key, auth_id, code).finalize().into_bytes())
+}
+
+pub fn verify_magic_token(key: &str, auth_id: &str, code: &str, token: &str) -> bool {
+    if !is_magic_token(token) {
+        return false;
+    }
+
+    match HEXLOWER.decode(token.as_bytes()) {
+        Ok(signature) => get_mac(key, auth_id, code).verify_slice(&signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+pub fn get_magic_link(state: &AppState, auth_id: &str, code: &str) -> String {
+    format_magic_link(
+        &state.config.svelte_url,
+        &state.config.magic_link_key,
+        auth_id,
+        code,
+    )
+}
+
+fn format_magic_link(svelte_url: &str, key: &str, auth_id: &str, code: &str) -> String {
+    format!(
+        "{}/auth/verify?token={}",
+        svelte_url,
+        get_magic_token(key, auth_id, code)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_link_token(link: &str) -> &str {
+        link.split_once("/auth/verify?token=")
+            .map(|(_, token)| token)
+            .expect("magic link token")
+    }
+
+    #[test]
+    fn magic_link_token_verifies_for_its_auth_id() {
+        let link = format_magic_link("https://boiler.swap", "key", "auth-id", "code-hash");
+        let token = get_link_token(&link);
+
+        assert!(link.starts_with("https://boiler.swap/auth/verify?token="));
+        assert!(is_magic_token(token));
+        assert!(verify_magic_token("key", "auth-id", "code-hash", token));
+        assert!(!verify_magic_token("key", "other-id", "code-hash", token));
+        assert!(!verify_magic_token(
+            "other-key",
+            "auth-id",
+            "code-hash",
+            token
+        ));
+    }
+}
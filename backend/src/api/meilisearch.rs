@@ -1,6 +1,6 @@
 use super::{
     database::{DatabaseQueries, convert_db_items},
-    models::ItemRow,
+    models::{Item, ItemRow},
     schema::{columns::items, tables},
 };
 use crate::{AppError, config::read_secret};
@@ -45,8 +45,13 @@ pub async fn init_meilisearch(
             "sort",
         ])
         .with_distinct_attribute(Some(items::ITEM_ID))
-        .with_searchable_attributes([items::TITLE, items::DESCRIPTION])
-        .with_filterable_attributes([items::ITEM_TYPE, items::CONDITION, items::LOCATION])
+        .with_searchable_attributes([items::TITLE, items::DESCRIPTION, items::AUTHOR])
+        .with_filterable_attributes([
+            items::ITEM_TYPE,
+            items::CONDITION,
+            items::LOCATION,
+            items::COURSE_CODE,
+        ])
         .with_typo_tolerance(TypoToleranceSettings {
             enabled: Some(true),
             disable_on_attributes: None,
@@ -148,6 +153,24 @@ pub async fn delete_item(
     Ok(())
 }
 
+pub async fn search_items(
+    meili_client: Arc<Client>,
+    index_name: &str,
+    query: &str,
+) -> Result<Vec<Item>, AppError> {
+    Ok(meili_client
+        .index(index_name)
+        .search()
+        .with_query(query)
+        .with_limit(20)
+        .execute::<Item>()
+        .await?
+        .hits
+        .into_iter()
+        .map(|hit| hit.result)
+        .collect())
+}
+
 pub async fn clear_index(meili_client: Arc<Client>, index_name: &str) -> anyhow::Result<()> {
     meili_client
         .index(index_name)
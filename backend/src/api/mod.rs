@@ -1,12 +1,29 @@
+pub mod account;
 pub mod consumer;
+pub mod csrf;
 pub mod database;
+pub mod drafts;
+pub mod email;
+pub mod export;
+pub mod found;
 pub mod handlers;
 pub mod lock;
+pub mod magiclink;
 pub mod meilisearch;
 pub mod models;
+pub mod notifications;
+pub mod oidc;
+pub mod passkeys;
+pub mod password;
+pub mod recovery;
 pub mod redis;
 pub mod schema;
 pub mod sessions;
+pub mod similarity;
+pub mod textbooks;
+pub mod throttle;
+pub mod tokens;
+pub mod totp;
 pub mod twofactor;
 pub mod utilities;
 pub mod verify;
@@ -1,14 +1,17 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, EnumString};
 use uuid::Uuid;
+use webauthn_rs::prelude::PasskeyAuthentication;
 
 #[derive(Serialize, Deserialize)]
 pub struct Account {
     pub email: String,
     pub password: String,
     pub action: Action,
+    #[serde(default)]
+    pub remember: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -19,6 +22,10 @@ pub struct RedisAccount {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub issued_timestamp: Option<i64>,
     pub password_hash: Option<String>,
+    #[serde(default)]
+    pub remember: bool,
+    #[serde(default)]
+    pub attempts: u8,
 }
 
 #[derive(Deserialize)]
@@ -26,6 +33,24 @@ pub struct Token {
     pub token: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct EmailChange {
+    pub current: RedisAccount,
+    pub new: RedisAccount,
+}
+
+#[derive(Deserialize)]
+pub struct EmailChangeCodes {
+    pub code: String,
+    pub new_code: String,
+}
+
+#[derive(Deserialize)]
+pub struct PasswordChange {
+    pub current_password: String,
+    pub new_password: String,
+}
+
 #[derive(Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 #[derive(Clone)]
@@ -58,12 +83,39 @@ pub enum RedisAction {
     #[strum(serialize = "sessions")]
     SessionStore,
 
+    #[strum(serialize = "session_devices")]
+    SessionDevices,
+
+    #[strum(serialize = "remembered_sessions")]
+    RememberedSessions,
+
+    #[strum(serialize = "session_fingerprints")]
+    SessionFingerprints,
+
+    #[strum(serialize = "oidc_id")]
+    OidcAuthentication,
+
+    #[strum(serialize = "known_devices")]
+    KnownDevices,
+
     #[strum(serialize = "verify_lock")]
     LockedVerify,
 
     #[strum(serialize = "auth_lock")]
     LockedAuth,
 
+    #[strum(serialize = "auth_ip_lock")]
+    LockedAuthIp,
+
+    #[strum(serialize = "auth_account_lock")]
+    LockedAuthAccount,
+
+    #[strum(serialize = "verify_ip_lock")]
+    LockedVerifyIp,
+
+    #[strum(serialize = "verify_account_lock")]
+    LockedVerifyAccount,
+
     #[strum(serialize = "forgot_lock")]
     LockedForgot,
 
@@ -73,12 +125,174 @@ pub enum RedisAction {
     #[strum(serialize = "item_lock")]
     LockedItems,
 
+    #[strum(serialize = "lock_keys")]
+    LockKeys,
+
+    #[strum(serialize = "publish_cursor")]
+    PublishCursor,
+
     #[strum(serialize = "item_deleted")]
     DeletedItem,
+
+    #[strum(serialize = "item_fingerprint")]
+    ItemFingerprint,
+
+    #[strum(serialize = "question_lock")]
+    LockedQuestions,
+
+    #[strum(serialize = "claim_lock")]
+    LockedClaim,
+
+    #[strum(serialize = "totp_pending")]
+    TotpPending,
+
+    #[strum(serialize = "totp_used")]
+    TotpUsed,
+
+    #[strum(serialize = "data_export")]
+    DataExport,
+
+    #[strum(serialize = "export_lock")]
+    LockedExport,
+
+    #[strum(serialize = "account_deletion")]
+    AccountDeletion,
+
+    #[strum(serialize = "email_change")]
+    EmailChange,
+
+    #[strum(serialize = "passkey_registration")]
+    PasskeyRegistration,
+
+    #[strum(serialize = "passkey_id")]
+    PasskeyAuthentication,
+}
+
+#[derive(EnumString, AsRefStr)]
+pub enum AuditEvent {
+    #[strum(serialize = "account_deleted")]
+    AccountDeleted,
+}
+
+#[derive(Deserialize)]
+pub struct ApiClaims {
+    pub exp: i64,
+    pub iat: i64,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryCodes {
+    pub codes: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TotpEnrollment {
+    pub provisioning_uri: String,
+    pub secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    pub mute_notices: bool,
+}
+
+#[derive(Serialize)]
+pub struct DataExport {
+    pub email: String,
+    pub locked: bool,
+    pub authenticator_enabled: bool,
+    pub recovery_codes_remaining: usize,
+    pub passkeys: usize,
+    pub mute_notices: bool,
+    pub items: Vec<Item>,
+    pub drafts: Vec<Draft>,
+    pub sessions: Vec<ActiveSession>,
+    pub exported_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ActiveSession {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub device: String,
+    pub current: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionFingerprint {
+    pub device: String,
+    #[serde(default)]
+    pub user_agent_hash: String,
+    pub hashed_ips: Vec<String>,
+}
+
+pub enum SessionCheck {
+    Trusted,
+    Challenged,
+    Revoked,
 }
 
 #[derive(Deserialize)]
-pub struct DummyClaims {}
+pub struct OidcStart {
+    pub provider: String,
+    #[serde(default)]
+    pub remember: bool,
+}
+
+#[derive(Deserialize)]
+pub struct OidcFinish {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Serialize)]
+pub struct OidcAuthorization {
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OidcAuthenticationState {
+    pub provider: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub remember: bool,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct OidcDiscovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+pub struct OidcTokenResponse {
+    pub id_token: String,
+}
+
+#[derive(Deserialize)]
+pub struct OidcClaims {
+    pub email: String,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub nonce: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PasskeyAuthenticationState {
+    pub email: String,
+    pub state: PasskeyAuthentication,
+    #[serde(default)]
+    pub remember: bool,
+}
+
+#[derive(Deserialize)]
+pub struct PasskeyStart {
+    pub token: String,
+    #[serde(default)]
+    pub remember: bool,
+}
 
 #[derive(TryFromPrimitive, Serialize, Deserialize, EnumString, AsRefStr)]
 #[repr(u8)]
@@ -103,6 +317,9 @@ pub enum ItemType {
 
     #[strum(serialize = "Decor")]
     Decor = 6,
+
+    #[strum(serialize = "Found")]
+    Found = 7,
 }
 
 #[derive(TryFromPrimitive, Serialize, Deserialize, EnumString, AsRefStr)]
@@ -170,9 +387,74 @@ pub struct ItemPayload {
     pub description: String,
     pub location: Location,
     pub emoji: Emoji,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub course_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct VerificationQuestion {
+    pub question: String,
+    pub answer: String,
+}
+
+#[derive(Deserialize)]
+pub struct FoundItemPayload {
+    #[serde(flatten)]
+    pub item: ItemPayload,
+    pub verification: Vec<VerificationQuestion>,
+}
+
+#[derive(Deserialize)]
+pub struct ClaimPayload {
+    pub item_id: Uuid,
+    pub answers: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Contact {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct DraftPayload {
+    #[serde(flatten)]
+    pub item: ItemPayload,
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Serialize)]
+pub struct Draft {
+    pub draft_id: Uuid,
+    #[serde(flatten)]
+    pub item: ItemPayload,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub blocked: bool,
+}
+
+#[derive(Deserialize)]
+pub struct QuestionPayload {
+    pub item_id: Uuid,
+    pub question: String,
+}
+
+#[derive(Deserialize)]
+pub struct AnswerPayload {
+    pub item_id: Uuid,
+    pub question_id: Uuid,
+    pub answer: String,
+}
+
+#[derive(Serialize)]
+pub struct Question {
+    pub question_id: Uuid,
+    pub question: String,
+    pub answer: Option<String>,
+    pub asked_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Item {
     pub item_id: Uuid,
     pub item_type: String,
@@ -182,6 +464,9 @@ pub struct Item {
     pub description: String,
     pub emoji: String,
     pub expiration_date: String,
+    pub isbn: Option<String>,
+    pub author: Option<String>,
+    pub course_code: Option<String>,
 }
 
 pub struct CronItem {
@@ -189,6 +474,36 @@ pub struct CronItem {
     pub expiration_date: NaiveDate,
 }
 
-pub type ItemRow<'a> = (Uuid, i8, &'a str, i8, i8, &'a str, i8, NaiveDate);
+pub type ItemRow<'a> = (
+    Uuid,
+    i8,
+    &'a str,
+    i8,
+    i8,
+    &'a str,
+    i8,
+    NaiveDate,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+);
 
 pub type CronItemRow<'a> = (Uuid, NaiveDate);
+
+pub type DraftRow<'a> = (
+    Uuid,
+    i8,
+    &'a str,
+    i8,
+    i8,
+    &'a str,
+    i8,
+    Option<DateTime<Utc>>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<bool>,
+);
+
+pub type ScheduledDraftRow<'a> = (DateTime<Utc>, &'a str, Uuid);
+
+pub type QuestionRow<'a> = (Uuid, &'a str, Option<&'a str>, DateTime<Utc>);
@@ -0,0 +1,85 @@
+use super::{
+    database::get_mute_notices,
+    email::{log_email_error, send_email},
+};
+use crate::AppState;
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::warn;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SecurityNotice {
+    NewSignIn,
+    NewLocation,
+    SuspiciousSession,
+    AccountFrozen,
+    PasswordChanged,
+}
+
+impl SecurityNotice {
+    pub fn is_critical(&self) -> bool {
+        !matches!(
+            self,
+            SecurityNotice::NewSignIn | SecurityNotice::NewLocation
+        )
+    }
+
+    fn subject(&self) -> &'static str {
+        match self {
+            SecurityNotice::NewSignIn => "BoilerSwap New Sign-in",
+            SecurityNotice::NewLocation => "BoilerSwap New Session Location",
+            SecurityNotice::SuspiciousSession => "BoilerSwap Suspicious Session",
+            SecurityNotice::AccountFrozen => "BoilerSwap Account Frozen",
+            SecurityNotice::PasswordChanged => "BoilerSwap Password Changed",
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            SecurityNotice::NewSignIn => "Your account was signed in from a new device.",
+            SecurityNotice::NewLocation => {
+                "One of your sessions was used from a new network. If this wasn't you, sign out of your other sessions from your account page."
+            }
+            SecurityNotice::SuspiciousSession => {
+                "One of your sessions was used from a different device and has been signed out."
+            }
+            SecurityNotice::AccountFrozen => {
+                "Your account was frozen and signed out everywhere because a password reset was requested."
+            }
+            SecurityNotice::PasswordChanged => "Your password was changed.",
+        }
+    }
+}
+
+pub fn get_sessions_link(svelte_url: &str) -> String {
+    format!("{}/account/sessions", svelte_url)
+}
+
+pub fn spawn_notice_task(
+    state: Arc<AppState>,
+    user_email: String,
+    notice: SecurityNotice,
+    device: String,
+) {
+    tokio::spawn(async move {
+        if !notice.is_critical() {
+            match get_mute_notices(state.clone(), &user_email).await {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to read notification preference: {}", e),
+            }
+        }
+
+        let body = format!(
+            "{}\n\nTime: {}\nDevice: {}\n\nIf this wasn't you, review and revoke your sessions: {}",
+            notice.message(),
+            Utc::now().format("%Y-%m-%d %H:%M UTC"),
+            device,
+            get_sessions_link(&state.config.svelte_url),
+        );
+
+        if let Err(error) = send_email(state.clone(), &user_email, notice.subject(), body).await {
+            log_email_error(error);
+        }
+    });
+}
@@ -0,0 +1,498 @@
+use super::{
+    database::{get_passkeys, insert_passkey},
+    lock::check_db_lock,
+    models::{Action, PasskeyAuthenticationState, PasskeyStart, RedisAccount, RedisAction},
+    redis::{increment_lock_key, insert_id, is_redis_locked, remove_id, try_get},
+    sessions::{create_session, generate_cookie, get_cookie},
+    utilities::{get_hashed_ip, get_key},
+    verify::{SessionUser, validate_email},
+};
+use crate::{AppError, AppState};
+use axum::{
+    Json,
+    extract::{ConnectInfo, State},
+    http::{StatusCode, header::HeaderMap},
+    response::IntoResponse,
+};
+use data_encoding::BASE64URL_NOPAD;
+use std::{net::SocketAddr, sync::Arc};
+use webauthn_rs::prelude::{
+    AuthenticationResult, CreationChallengeResponse, CredentialID, Passkey, PasskeyRegistration,
+    PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse, Url, Uuid,
+    Webauthn, WebauthnBuilder,
+};
+
+pub const RP_NAME: &str = "BoilerSwap";
+
+pub fn init_webauthn(svelte_url: &str) -> Result<Webauthn, AppError> {
+    let rp_origin =
+        Url::parse(svelte_url).map_err(|_| AppError::Config("Invalid SVELTE_URL value".into()))?;
+
+    let rp_id = rp_origin
+        .domain()
+        .ok_or_else(|| AppError::Config("Invalid SVELTE_URL domain".into()))?
+        .to_string();
+
+    Ok(WebauthnBuilder::new(&rp_id, &rp_origin)?
+        .rp_name(RP_NAME)
+        .build()?)
+}
+
+pub fn get_user_handle(email: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, email.as_bytes())
+}
+
+pub fn get_credential_id(credential_id: &CredentialID) -> String {
+    BASE64URL_NOPAD.encode(credential_id.as_ref())
+}
+
+pub fn start_registration(
+    webauthn: &Webauthn,
+    email: &str,
+    passkeys: &[Passkey],
+) -> Result<(CreationChallengeResponse, String), AppError> {
+    let exclude_credentials = passkeys
+        .iter()
+        .map(|passkey| passkey.cred_id().clone())
+        .collect::<Vec<_>>();
+
+    let (challenge, registration_state) = webauthn.start_passkey_registration(
+        get_user_handle(email),
+        email,
+        email,
+        Some(exclude_credentials),
+    )?;
+
+    Ok((challenge, serde_json::to_string(&registration_state)?))
+}
+
+pub fn finish_registration(
+    webauthn: &Webauthn,
+    registration_state: &str,
+    credential: &RegisterPublicKeyCredential,
+) -> Result<Option<Passkey>, AppError> {
+    let registration_state: PasskeyRegistration = serde_json::from_str(registration_state)?;
+
+    Ok(webauthn
+        .finish_passkey_registration(credential, &registration_state)
+        .ok())
+}
+
+pub fn start_authentication(
+    webauthn: &Webauthn,
+    email: String,
+    remember: bool,
+    passkeys: &[Passkey],
+) -> Result<(RequestChallengeResponse, String), AppError> {
+    let (challenge, authentication_state) = webauthn.start_passkey_authentication(passkeys)?;
+
+    Ok((
+        challenge,
+        serde_json::to_string(&PasskeyAuthenticationState {
+            email,
+            state: authentication_state,
+            remember,
+        })?,
+    ))
+}
+
+pub fn finish_authentication(
+    webauthn: &Webauthn,
+    authentication_state: &PasskeyAuthenticationState,
+    credential: &PublicKeyCredential,
+) -> Option<AuthenticationResult> {
+    webauthn
+        .finish_passkey_authentication(credential, &authentication_state.state)
+        .ok()
+}
+
+pub fn get_updated_passkey(
+    passkeys: Vec<Passkey>,
+    authentication_result: &AuthenticationResult,
+) -> Option<Passkey> {
+    if !authentication_result.needs_update() {
+        return None;
+    }
+
+    passkeys.into_iter().find_map(|mut passkey| {
+        (passkey.cred_id() == authentication_result.cred_id()
+            && passkey.update_credential(authentication_result) == Some(true))
+        .then_some(passkey)
+    })
+}
+
+pub async fn update_passkey_counter(
+    state: Arc<AppState>,
+    authentication_state: &PasskeyAuthenticationState,
+    authentication_result: &AuthenticationResult,
+) -> Result<(), AppError> {
+    if !authentication_result.needs_update() {
+        return Ok(());
+    }
+
+    let passkeys = get_passkeys(state.clone(), &authentication_state.email).await?;
+
+    if let Some(passkey) = get_updated_passkey(passkeys, authentication_result) {
+        insert_passkey(state.clone(), &authentication_state.email, &passkey).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn passkey_register_start_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+) -> Result<impl IntoResponse, AppError> {
+    let (challenge, registration_state) = start_registration(
+        &state.webauthn,
+        &email,
+        &get_passkeys(state.clone(), &email).await?,
+    )?;
+
+    insert_id(
+        state.clone(),
+        RedisAction::PasskeyRegistration.as_ref(),
+        &email,
+        &registration_state,
+        state.config.temporary_session_duration_seconds.into(),
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(challenge)).into_response())
+}
+
+pub async fn passkey_register_finish_handler(
+    State(state): State<Arc<AppState>>,
+    SessionUser { email }: SessionUser,
+    Json(payload): Json<RegisterPublicKeyCredential>,
+) -> Result<impl IntoResponse, AppError> {
+    let registration_state = match try_get(
+        state.clone(),
+        RedisAction::PasskeyRegistration.as_ref(),
+        &email,
+    )
+    .await?
+    {
+        Some(registration_state) => registration_state,
+        None => {
+            return Ok((StatusCode::BAD_REQUEST, "Passkey registration expired").into_response());
+        }
+    };
+
+    remove_id(
+        state.clone(),
+        RedisAction::PasskeyRegistration.as_ref(),
+        &email,
+    )
+    .await?;
+
+    let passkey = match finish_registration(&state.webauthn, &registration_state, &payload)? {
+        Some(passkey) => passkey,
+        None => {
+            return Ok((StatusCode::BAD_REQUEST, "Invalid passkey").into_response());
+        }
+    };
+
+    insert_passkey(state.clone(), &email, &passkey).await?;
+
+    Ok((StatusCode::OK).into_response())
+}
+
+pub async fn passkey_login_start_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<PasskeyStart>,
+) -> Result<impl IntoResponse, AppError> {
+    let email = payload.token;
+
+    if let Err(e) = validate_email(&email) {
+        return Ok((StatusCode::BAD_REQUEST, e).into_response());
+    }
+
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let failed_auth_key = get_key(RedisAction::LockedAuth, &hashed_ip);
+
+    if is_redis_locked(
+        state.clone(),
+        &failed_auth_key,
+        &email,
+        &state.config.auth_max_attempts,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    let passkeys = get_passkeys(state.clone(), &email).await?;
+
+    if passkeys.is_empty() || check_db_lock(state.clone(), &email).await? {
+        increment_lock_key(
+            state.clone(),
+            &failed_auth_key,
+            &email,
+            &state.config.auth_lock_duration_seconds,
+            &state.config.auth_max_attempts,
+        )
+        .await?;
+        return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+    }
+
+    let (challenge, authentication_state) =
+        start_authentication(&state.webauthn, email, payload.remember, &passkeys)?;
+
+    let id = Uuid::new_v4().to_string();
+    let ttl = state.config.temporary_session_duration_seconds;
+
+    insert_id(
+        state.clone(),
+        RedisAction::PasskeyAuthentication.as_ref(),
+        &id,
+        &authentication_state,
+        ttl.into(),
+    )
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        generate_cookie(RedisAction::PasskeyAuthentication.as_ref(), &id, ttl.into()),
+        Json(challenge),
+    )
+        .into_response())
+}
+
+pub async fn passkey_login_finish_handler(
+    headers: HeaderMap,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<PublicKeyCredential>,
+) -> Result<impl IntoResponse, AppError> {
+    let id = match get_cookie(&headers, RedisAction::PasskeyAuthentication.as_ref()) {
+        Some(id) => id,
+        None => {
+            return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+        }
+    };
+
+    let authentication_state = match try_get(
+        state.clone(),
+        RedisAction::PasskeyAuthentication.as_ref(),
+        &id,
+    )
+    .await?
+    {
+        Some(authentication_state) => {
+            serde_json::from_str::<PasskeyAuthenticationState>(&authentication_state)?
+        }
+        None => {
+            return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+        }
+    };
+
+    remove_id(
+        state.clone(),
+        RedisAction::PasskeyAuthentication.as_ref(),
+        &id,
+    )
+    .await?;
+
+    let hashed_ip = get_hashed_ip(&state.config, &headers, address.ip());
+    let failed_auth_key = get_key(RedisAction::LockedAuth, &hashed_ip);
+    let email = authentication_state.email.clone();
+
+    if is_redis_locked(
+        state.clone(),
+        &failed_auth_key,
+        &email,
+        &state.config.auth_max_attempts,
+    )
+    .await?
+    {
+        return Ok((StatusCode::UNAUTHORIZED, "Try again in 30 minutes").into_response());
+    }
+
+    let authentication_result =
+        match finish_authentication(&state.webauthn, &authentication_state, &payload) {
+            Some(authentication_result) if !check_db_lock(state.clone(), &email).await? => {
+                authentication_result
+            }
+            _ => {
+                increment_lock_key(
+                    state.clone(),
+                    &failed_auth_key,
+                    &email,
+                    &state.config.auth_lock_duration_seconds,
+                    &state.config.auth_max_attempts,
+                )
+                .await?;
+                return Ok((StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response());
+            }
+        };
+
+    update_passkey_counter(state.clone(), &authentication_state, &authentication_result).await?;
+
+    remove_id(state.clone(), &failed_auth_key, &email).await?;
+
+    Ok((
+        StatusCode::OK,
+        create_session(
+            state.clone(),
+            &RedisAccount {
+                email,
+                action: Action::Login,
+                code: String::new(),
+                issued_timestamp: None,
+                password_hash: None,
+                remember: authentication_state.remember,
+                attempts: 0,
+            },
+            RedisAction::Session,
+            RedisAction::SessionStore,
+            &headers,
+            address.ip(),
+        )
+        .await?,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webauthn_authenticator_rs::{WebauthnAuthenticator, softpasskey::SoftPasskey};
+
+    const ORIGIN: &str = "https://boilerswap.test";
+    const EMAIL: &str = "student@purdue.edu";
+
+    fn get_origin() -> Url {
+        Url::parse(ORIGIN).unwrap()
+    }
+
+    fn register(
+        webauthn: &Webauthn,
+        authenticator: &mut WebauthnAuthenticator<SoftPasskey>,
+        passkeys: &[Passkey],
+    ) -> Option<Passkey> {
+        let (challenge, registration_state) =
+            start_registration(webauthn, EMAIL, passkeys).unwrap();
+
+        let credential = authenticator
+            .do_registration(get_origin(), challenge)
+            .ok()?;
+
+        finish_registration(webauthn, &registration_state, &credential).unwrap()
+    }
+
+    fn authenticate(
+        webauthn: &Webauthn,
+        authenticator: &mut WebauthnAuthenticator<SoftPasskey>,
+        passkeys: &[Passkey],
+    ) -> Option<AuthenticationResult> {
+        let (challenge, authentication_state) =
+            start_authentication(webauthn, EMAIL.to_string(), false, passkeys).unwrap();
+
+        let authentication_state: PasskeyAuthenticationState =
+            serde_json::from_str(&authentication_state).unwrap();
+
+        assert_eq!(authentication_state.email, EMAIL);
+
+        let credential = authenticator
+            .do_authentication(get_origin(), challenge)
+            .ok()?;
+
+        finish_authentication(webauthn, &authentication_state, &credential)
+    }
+
+    #[test]
+    fn registers_and_authenticates_with_software_authenticator() {
+        let webauthn = init_webauthn(ORIGIN).unwrap();
+        let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let passkey = register(&webauthn, &mut authenticator, &[]).expect("registration succeeds");
+        let authentication_result = authenticate(
+            &webauthn,
+            &mut authenticator,
+            std::slice::from_ref(&passkey),
+        )
+        .expect("authentication succeeds");
+
+        assert_eq!(authentication_result.cred_id(), passkey.cred_id());
+    }
+
+    #[test]
+    fn rejects_credential_for_another_ceremony() {
+        let webauthn = init_webauthn(ORIGIN).unwrap();
+        let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let (challenge, _) = start_registration(&webauthn, EMAIL, &[]).unwrap();
+        let (_, other_registration_state) = start_registration(&webauthn, EMAIL, &[]).unwrap();
+
+        let credential = authenticator
+            .do_registration(get_origin(), challenge)
+            .unwrap();
+
+        assert!(
+            finish_registration(&webauthn, &other_registration_state, &credential)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn rejects_unregistered_authenticator() {
+        let webauthn = init_webauthn(ORIGIN).unwrap();
+        let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+        let mut other_authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let passkey = register(&webauthn, &mut authenticator, &[]).expect("registration succeeds");
+
+        assert!(authenticate(&webauthn, &mut other_authenticator, &[passkey]).is_none());
+    }
+
+    #[test]
+    fn rejects_credential_from_another_relying_party() {
+        let webauthn = init_webauthn(ORIGIN).unwrap();
+        let other_webauthn = init_webauthn("https://elsewhere.test").unwrap();
+        let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let passkey = register(&webauthn, &mut authenticator, &[]).expect("registration succeeds");
+
+        let (challenge, authentication_state) =
+            start_authentication(&webauthn, EMAIL.to_string(), false, &[passkey]).unwrap();
+        let authentication_state: PasskeyAuthenticationState =
+            serde_json::from_str(&authentication_state).unwrap();
+
+        let credential = authenticator
+            .do_authentication(get_origin(), challenge)
+            .unwrap();
+
+        assert!(
+            finish_authentication(&other_webauthn, &authentication_state, &credential).is_none()
+        );
+    }
+
+    #[test]
+    fn updates_stored_counter_after_authentication() {
+        let webauthn = init_webauthn(ORIGIN).unwrap();
+        let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let passkey = register(&webauthn, &mut authenticator, &[]).expect("registration succeeds");
+        let authentication_result = authenticate(
+            &webauthn,
+            &mut authenticator,
+            std::slice::from_ref(&passkey),
+        )
+        .expect("authentication succeeds");
+
+        let updated =
+            get_updated_passkey(vec![passkey], &authentication_result).expect("counter is updated");
+
+        assert!(authenticate(&webauthn, &mut authenticator, &[updated]).is_some());
+    }
+
+    #[test]
+    fn derives_stable_user_handles() {
+        assert_eq!(get_user_handle(EMAIL), get_user_handle(EMAIL));
+        assert_ne!(get_user_handle(EMAIL), get_user_handle("other@purdue.edu"));
+    }
+}
@@ -0,0 +1,213 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{collections::HashSet, env};
+
+pub const MIN_PASSWORD_SCORE: u8 = 2;
+
+pub static MIN_PASSWORD_LENGTH: Lazy<usize> = Lazy::new(|| {
+    env::var("PUBLIC_MIN_PASSWORD_LENGTH")
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(10)
+});
+pub static MAX_PASSWORD_LENGTH: Lazy<usize> = Lazy::new(|| {
+    env::var("PUBLIC_MAX_PASSWORD_LENGTH")
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(128)
+});
+pub static BREACHED_PASSWORDS: Lazy<HashSet<String>> = Lazy::new(|| {
+    include_str!("../../data/breached_passwords.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+});
+
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum PasswordRejection {
+    Empty,
+    TooShort { min: usize },
+    TooLong { max: usize },
+    TooWeak { score: u8, min_score: u8 },
+    Breached,
+    ContainsEmail,
+}
+
+#[derive(Serialize)]
+pub struct PasswordRejections {
+    pub reasons: Vec<PasswordRejection>,
+}
+
+fn get_pool_size(password: &str) -> f64 {
+    let mut pool = 0;
+
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if password
+        .chars()
+        .any(|c| c.is_ascii_punctuation() || c == ' ')
+    {
+        pool += 33;
+    }
+    if !password.is_ascii() {
+        pool += 100;
+    }
+
+    pool.max(1) as f64
+}
+
+pub fn estimate_strength(password: &str) -> u8 {
+    let chars: Vec<char> = password.chars().flat_map(char::to_lowercase).collect();
+
+    let effective_length = chars
+        .iter()
+        .enumerate()
+        .filter(|(i, c)| {
+            *i == 0 || {
+                let previous = chars[i - 1] as i64;
+                let current = **c as i64;
+                (current - previous).abs() > 1
+            }
+        })
+        .count();
+
+    let bits = effective_length as f64 * get_pool_size(password).log2();
+
+    match bits {
+        b if b < 28.0 => 0,
+        b if b < 36.0 => 1,
+        b if b < 60.0 => 2,
+        b if b < 80.0 => 3,
+        _ => 4,
+    }
+}
+
+pub fn is_breached(password: &str) -> bool {
+    let normalized = password.trim().to_lowercase();
+    let stripped =
+        normalized.trim_end_matches(|c: char| c.is_ascii_digit() || c.is_ascii_punctuation());
+
+    BREACHED_PASSWORDS.contains(&normalized)
+        || (!stripped.is_empty() && BREACHED_PASSWORDS.contains(stripped))
+}
+
+pub fn check_password(password: &str, email: Option<&str>) -> Vec<PasswordRejection> {
+    let length = password.chars().count();
+
+    if length == 0 {
+        return vec![PasswordRejection::Empty];
+    }
+
+    if length > *MAX_PASSWORD_LENGTH {
+        return vec![PasswordRejection::TooLong {
+            max: *MAX_PASSWORD_LENGTH,
+        }];
+    }
+
+    let mut reasons = Vec::new();
+
+    if length < *MIN_PASSWORD_LENGTH {
+        reasons.push(PasswordRejection::TooShort {
+            min: *MIN_PASSWORD_LENGTH,
+        });
+    }
+
+    let score = estimate_strength(password);
+    if score < MIN_PASSWORD_SCORE {
+        reasons.push(PasswordRejection::TooWeak {
+            score,
+            min_score: MIN_PASSWORD_SCORE,
+        });
+    }
+
+    if is_breached(password) {
+        reasons.push(PasswordRejection::Breached);
+    }
+
+    if let Some(local) = email.and_then(|email| email.split('@').next())
+        && local.chars().count() >= 3
+        && password.to_lowercase().contains(&local.to_lowercase())
+    {
+        reasons.push(PasswordRejection::ContainsEmail);
+    }
+
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRONG_PASSWORD: &str = "Violet-Harbor-Tram-91";
+
+    #[test]
+    fn accepts_strong_password() {
+        assert!(check_password(STRONG_PASSWORD, Some("jsmith@purdue.edu")).is_empty());
+    }
+
+    #[test]
+    fn rejects_empty_password() {
+        assert_eq!(check_password("", None), vec![PasswordRejection::Empty]);
+    }
+
+    #[test]
+    fn counts_length_in_characters() {
+        let short = "\u{e9}\u{f8}".repeat(*MIN_PASSWORD_LENGTH / 2 - 1);
+        let long = "\u{e9}".repeat(*MAX_PASSWORD_LENGTH);
+
+        assert!(short.len() >= *MIN_PASSWORD_LENGTH);
+        assert!(
+            check_password(&short, None).contains(&PasswordRejection::TooShort {
+                min: *MIN_PASSWORD_LENGTH
+            })
+        );
+        assert!(
+            !check_password(&long, None).contains(&PasswordRejection::TooLong {
+                max: *MAX_PASSWORD_LENGTH
+            })
+        );
+        assert_eq!(
+            check_password(&format!("{}a", long), None),
+            vec![PasswordRejection::TooLong {
+                max: *MAX_PASSWORD_LENGTH
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_email_local_part() {
+        assert!(
+            check_password("Violet-JSmith-Tram-91", Some("jsmith@purdue.edu"))
+                .contains(&PasswordRejection::ContainsEmail)
+        );
+        assert!(
+            !check_password(STRONG_PASSWORD, Some("vi@purdue.edu"))
+                .contains(&PasswordRejection::ContainsEmail)
+        );
+    }
+
+    #[test]
+    fn rejects_breached_passwords() {
+        assert!(check_password("password", None).contains(&PasswordRejection::Breached));
+        assert!(check_password("Password123!", None).contains(&PasswordRejection::Breached));
+        assert!(!is_breached(STRONG_PASSWORD));
+    }
+
+    #[test]
+    fn scores_sequences_and_variety() {
+        assert_eq!(estimate_strength("aaaaaaaaaaaa"), 0);
+        assert_eq!(estimate_strength("abcdefghijkl"), 0);
+        assert!(estimate_strength("correct horse battery staple") >= 4);
+        assert!(estimate_strength(STRONG_PASSWORD) >= MIN_PASSWORD_SCORE);
+    }
+}
@@ -0,0 +1,91 @@
+use super::{
+    database::{get_recovery_codes, replace_recovery_codes},
+    verify::{hash_password, verify_password},
+};
+use crate::{AppError, AppState};
+use once_cell::sync::Lazy;
+use rand::{Rng, rngs::OsRng};
+use regex::Regex;
+use std::sync::Arc;
+use tokio::task::spawn_blocking;
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+pub const RECOVERY_CODE_LENGTH: usize = 10;
+pub const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+pub static RECOVERY_CODE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[a-z2-9]{5}-?[a-z2-9]{5}$").unwrap());
+
+pub fn generate_recovery_code() -> String {
+    let code: String = (0..RECOVERY_CODE_LENGTH)
+        .map(|_| RECOVERY_CODE_ALPHABET[OsRng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+
+    format!(
+        "{}-{}",
+        &code[..RECOVERY_CODE_LENGTH / 2],
+        &code[RECOVERY_CODE_LENGTH / 2..]
+    )
+}
+
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.trim().to_lowercase().replace('-', "")
+}
+
+pub fn is_recovery_code(code: &str) -> bool {
+    RECOVERY_CODE_REGEX.is_match(&code.trim().to_lowercase())
+}
+
+pub async fn store_recovery_codes(
+    state: Arc<AppState>,
+    email: &str,
+) -> Result<Vec<String>, AppError> {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+
+    let normalized: Vec<String> = codes
+        .iter()
+        .map(|code| normalize_recovery_code(code))
+        .collect();
+
+    let code_hashes = spawn_blocking(move || {
+        normalized
+            .iter()
+            .map(|code| hash_password(code))
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await??;
+
+    replace_recovery_codes(state.clone(), email, &code_hashes).await?;
+
+    Ok(codes)
+}
+
+pub async fn find_recovery_code(
+    state: Arc<AppState>,
+    email: &str,
+    code: &str,
+) -> Result<Option<String>, AppError> {
+    if !is_recovery_code(code) {
+        return Ok(None);
+    }
+
+    let code_hashes = get_recovery_codes(state.clone(), email).await?;
+
+    if code_hashes.is_empty() {
+        return Ok(None);
+    }
+
+    let normalized = normalize_recovery_code(code);
+
+    spawn_blocking(move || {
+        for code_hash in code_hashes {
+            if verify_password(&normalized, &code_hash)? {
+                return Ok(Some(code_hash));
+            }
+        }
+        Ok::<_, AppError>(None)
+    })
+    .await?
+}
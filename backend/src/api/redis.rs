@@ -1,12 +1,24 @@
 use super::{
-    database::{get_user, insert_item},
+    database::{
+        delete_draft, get_draft, get_expiration_days, get_user, insert_item, remove_recovery_code,
+        update_password,
+    },
     lock::check_locks,
-    models::{Action, ItemPayload, RedisAccount, RedisAction},
-    twofactor::generate_code,
-    verify::{hash_password, verify_password},
+    magiclink::verify_magic_token,
+    models::{
+        Action, ActiveSession, ItemPayload, RedisAccount, RedisAction, SessionCheck,
+        SessionFingerprint,
+    },
+    notifications::{SecurityNotice, spawn_notice_task},
+    recovery::find_recovery_code,
+    similarity::get_fingerprint,
+    totp::verify_user_totp,
+    twofactor::{generate_code, verify_code},
+    utilities::get_hash,
+    verify::{needs_rehash, spawn_hash_password, spawn_verify_password},
 };
 use crate::{AppError, AppState};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
 use redis::{
     AsyncTypedCommands, Client, Script,
@@ -17,8 +29,10 @@ use std::{
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use tokio::task::spawn_blocking;
 use tracing::warn;
+use uuid::Uuid;
+
+const MAX_SESSION_IPS: usize = 16;
 
 static FAILED_ATTEMPTS_SCRIPT: Lazy<Script> = Lazy::new(|| {
     Script::new(
@@ -31,6 +45,12 @@ static FAILED_ATTEMPTS_SCRIPT: Lazy<Script> = Lazy::new(|| {
         else
             redis.call("DECR", KEYS[1])
         end
+        if #KEYS > 1 and tonumber(ARGV[1]) > 0 then
+            redis.call("SADD", KEYS[2], KEYS[1])
+            if redis.call("TTL", KEYS[2]) < tonumber(ARGV[1]) then
+                redis.call("EXPIRE", KEYS[2], tonumber(ARGV[1]))
+            end
+        end
     "#,
     )
 });
@@ -46,6 +66,24 @@ static DECR_ITEMS_SCRIPT: Lazy<Script> = Lazy::new(|| {
     )
 });
 
+static MIGRATE_ACCOUNT_SCRIPT: Lazy<Script> = Lazy::new(|| {
+    Script::new(
+        r#"
+        for _, item_id in ipairs(redis.call("HKEYS", KEYS[1])) do
+            redis.call("SET", ARGV[2] .. ":" .. item_id, ARGV[1], "XX", "KEEPTTL")
+        end
+        for _, session_id in ipairs(redis.call("ZRANGE", KEYS[3], 0, -1)) do
+            redis.call("SET", ARGV[3] .. ":" .. session_id, ARGV[1], "XX", "KEEPTTL")
+        end
+        for i = 1, #KEYS, 2 do
+            if redis.call("EXISTS", KEYS[i]) == 1 then
+                redis.call("RENAME", KEYS[i], KEYS[i + 1])
+            end
+        end
+    "#,
+    )
+});
+
 pub async fn init_redis() -> Result<ConnectionManager, AppError> {
     let redis_url = env::var("RUST_REDIS_URL").unwrap_or_else(|_| {
         warn!("Environment variable RUST_REDIS_URL not found, using default");
@@ -80,6 +118,24 @@ pub async fn delete_all_sessions(
     }
 
     pipe.del(format!("{}:{}", key_secondary, email)).ignore();
+    pipe.del(format!(
+        "{}:{}",
+        RedisAction::SessionDevices.as_ref(),
+        email
+    ))
+    .ignore();
+    pipe.del(format!(
+        "{}:{}",
+        RedisAction::SessionFingerprints.as_ref(),
+        email
+    ))
+    .ignore();
+    pipe.del(format!(
+        "{}:{}",
+        RedisAction::RememberedSessions.as_ref(),
+        email
+    ))
+    .ignore();
 
     pipe.query_async::<()>(&mut state.redis_connection_manager.clone())
         .await?;
@@ -93,16 +149,23 @@ pub async fn insert_session(
     session_id: &str,
     key_secondary: &str,
     email: &str,
+    fingerprint: &SessionFingerprint,
+    remember: bool,
 ) -> Result<(), AppError> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("Time went backwards")
         .as_secs_f64();
 
+    let ttl = match remember {
+        true => state.config.remember_session_duration_seconds,
+        false => state.config.session_duration_seconds.into(),
+    };
+
     state
         .redis_connection_manager
         .clone()
-        .set_ex(format!("{}:{}", key, session_id), email, 3600)
+        .set_ex(format!("{}:{}", key, session_id), email, ttl.into())
         .await?;
 
     state
@@ -111,6 +174,54 @@ pub async fn insert_session(
         .zadd(format!("{}:{}", key_secondary, email), session_id, now)
         .await?;
 
+    state
+        .redis_connection_manager
+        .clone()
+        .hset(
+            format!("{}:{}", RedisAction::SessionDevices.as_ref(), email),
+            session_id,
+            &fingerprint.device,
+        )
+        .await?;
+
+    state
+        .redis_connection_manager
+        .clone()
+        .hset(
+            format!("{}:{}", RedisAction::SessionFingerprints.as_ref(), email),
+            session_id,
+            serde_json::to_string(fingerprint)?,
+        )
+        .await?;
+
+    if remember {
+        let remembered_key = format!("{}:{}", RedisAction::RememberedSessions.as_ref(), email);
+
+        state
+            .redis_connection_manager
+            .clone()
+            .zadd(&remembered_key, session_id, now)
+            .await?;
+
+        if state
+            .redis_connection_manager
+            .clone()
+            .zcard(&remembered_key)
+            .await?
+            > state.config.max_remembered_sessions.into()
+        {
+            let evicted = state
+                .redis_connection_manager
+                .clone()
+                .zpopmin(&remembered_key, 1)
+                .await?;
+
+            if let Some(evicted_id) = evicted.first() {
+                revoke_session(state.clone(), key, key_secondary, evicted_id, email).await?;
+            }
+        }
+    }
+
     if state
         .redis_connection_manager
         .clone()
@@ -118,16 +229,387 @@ pub async fn insert_session(
         .await?
         > state.config.max_sessions.into()
     {
-        state
+        let evicted = state
             .redis_connection_manager
             .clone()
-            .zremrangebyrank(format!("{}:{}", key_secondary, email), 0, 0)
+            .zpopmin(format!("{}:{}", key_secondary, email), 1)
             .await?;
+
+        if let Some(evicted_id) = evicted.first() {
+            remove_session(state.clone(), key, evicted_id, email).await?;
+        }
     }
 
     Ok(())
 }
 
+pub async fn refresh_session(
+    state: Arc<AppState>,
+    key: &str,
+    session_id: &str,
+    key_secondary: &str,
+    email: &str,
+) -> Result<bool, AppError> {
+    let created_at = match state
+        .redis_connection_manager
+        .clone()
+        .zscore(format!("{}:{}", key_secondary, email), session_id)
+        .await?
+    {
+        Some(created_at) => created_at as i64,
+        None => return Ok(false),
+    };
+
+    let (max_duration_seconds, idle_duration_seconds) =
+        match is_remembered_session(state.clone(), session_id, email).await? {
+            true => (
+                state.config.remember_session_max_duration_seconds,
+                state.config.remember_session_duration_seconds,
+            ),
+            false => (
+                state.config.session_max_duration_seconds,
+                state.config.session_duration_seconds.into(),
+            ),
+        };
+
+    let remaining = created_at + i64::from(max_duration_seconds) - Utc::now().timestamp();
+
+    if remaining <= 0 {
+        revoke_session(state.clone(), key, key_secondary, session_id, email).await?;
+        return Ok(false);
+    }
+
+    state
+        .redis_connection_manager
+        .clone()
+        .expire(
+            format!("{}:{}", key, session_id),
+            remaining.min(idle_duration_seconds.into()),
+        )
+        .await?;
+
+    Ok(true)
+}
+
+pub async fn is_remembered_session(
+    state: Arc<AppState>,
+    session_id: &str,
+    email: &str,
+) -> Result<bool, AppError> {
+    Ok(state
+        .redis_connection_manager
+        .clone()
+        .zscore(
+            format!("{}:{}", RedisAction::RememberedSessions.as_ref(), email),
+            session_id,
+        )
+        .await?
+        .is_some())
+}
+
+pub async fn remove_session(
+    state: Arc<AppState>,
+    key: &str,
+    session_id: &str,
+    email: &str,
+) -> Result<(), AppError> {
+    let mut pipe = redis::pipe();
+
+    pipe.del(format!("{}:{}", key, session_id)).ignore();
+    pipe.hdel(
+        format!("{}:{}", RedisAction::SessionDevices.as_ref(), email),
+        session_id,
+    )
+    .ignore();
+    pipe.hdel(
+        format!("{}:{}", RedisAction::SessionFingerprints.as_ref(), email),
+        session_id,
+    )
+    .ignore();
+    pipe.zrem(
+        format!("{}:{}", RedisAction::RememberedSessions.as_ref(), email),
+        session_id,
+    )
+    .ignore();
+
+    pipe.query_async::<()>(&mut state.redis_connection_manager.clone())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_active_sessions(
+    state: Arc<AppState>,
+    key: &str,
+    key_secondary: &str,
+    email: &str,
+    current_session_id: &str,
+) -> Result<Vec<ActiveSession>, AppError> {
+    let devices = state
+        .redis_connection_manager
+        .clone()
+        .hgetall(format!(
+            "{}:{}",
+            RedisAction::SessionDevices.as_ref(),
+            email
+        ))
+        .await?;
+
+    let mut active_sessions = Vec::new();
+
+    for (session_id, created_at) in state
+        .redis_connection_manager
+        .clone()
+        .zrange_withscores(format!("{}:{}", key_secondary, email), 0, -1)
+        .await?
+    {
+        if !state
+            .redis_connection_manager
+            .clone()
+            .exists(format!("{}:{}", key, session_id))
+            .await?
+        {
+            revoke_session(state.clone(), key, key_secondary, &session_id, email).await?;
+            continue;
+        }
+
+        active_sessions.push(ActiveSession {
+            id: get_hash(&session_id),
+            created_at: DateTime::from_timestamp(created_at as i64, 0).unwrap_or_default(),
+            device: devices
+                .get(&session_id)
+                .cloned()
+                .unwrap_or_else(|| "Unknown device".to_string()),
+            current: session_id == current_session_id,
+        });
+    }
+
+    Ok(active_sessions)
+}
+
+pub async fn revoke_session(
+    state: Arc<AppState>,
+    key: &str,
+    key_secondary: &str,
+    session_id: &str,
+    email: &str,
+) -> Result<(), AppError> {
+    state
+        .redis_connection_manager
+        .clone()
+        .zrem(format!("{}:{}", key_secondary, email), session_id)
+        .await?;
+
+    remove_session(state.clone(), key, session_id, email).await
+}
+
+pub async fn check_session_fingerprint(
+    state: Arc<AppState>,
+    key: &str,
+    key_secondary: &str,
+    session_id: &str,
+    email: &str,
+    fingerprint: SessionFingerprint,
+) -> Result<SessionCheck, AppError> {
+    let fingerprints_key = format!("{}:{}", RedisAction::SessionFingerprints.as_ref(), email);
+
+    let mut stored: SessionFingerprint = match state
+        .redis_connection_manager
+        .clone()
+        .hget(&fingerprints_key, session_id)
+        .await?
+        .and_then(|serialized| serde_json::from_str::<SessionFingerprint>(&serialized).ok())
+    {
+        Some(stored) if !stored.user_agent_hash.is_empty() => stored,
+        _ => {
+            revoke_session(state.clone(), key, key_secondary, session_id, email).await?;
+            return Ok(SessionCheck::Challenged);
+        }
+    };
+
+    if stored.user_agent_hash != fingerprint.user_agent_hash {
+        revoke_session(state.clone(), key, key_secondary, session_id, email).await?;
+
+        spawn_notice_task(
+            state.clone(),
+            email.to_string(),
+            SecurityNotice::SuspiciousSession,
+            fingerprint.device,
+        );
+
+        return Ok(SessionCheck::Revoked);
+    }
+
+    let new_ips: Vec<String> = fingerprint
+        .hashed_ips
+        .into_iter()
+        .filter(|hashed_ip| !stored.hashed_ips.contains(hashed_ip))
+        .collect();
+
+    if new_ips.is_empty() {
+        return Ok(SessionCheck::Trusted);
+    }
+
+    stored.hashed_ips.extend(new_ips);
+
+    let overflow = stored.hashed_ips.len().saturating_sub(MAX_SESSION_IPS);
+    stored.hashed_ips.drain(..overflow);
+
+    state
+        .redis_connection_manager
+        .clone()
+        .hset(
+            &fingerprints_key,
+            session_id,
+            serde_json::to_string(&stored)?,
+        )
+        .await?;
+
+    spawn_notice_task(
+        state.clone(),
+        email.to_string(),
+        SecurityNotice::NewLocation,
+        stored.device,
+    );
+
+    Ok(SessionCheck::Trusted)
+}
+
+pub async fn remember_device(
+    state: Arc<AppState>,
+    email: &str,
+    user_agent: &str,
+) -> Result<bool, AppError> {
+    let key = format!("{}:{}", RedisAction::KnownDevices.as_ref(), email);
+
+    let (added, known): (usize, usize) = redis::pipe()
+        .atomic()
+        .sadd(&key, user_agent)
+        .scard(&key)
+        .query_async(&mut state.redis_connection_manager.clone())
+        .await?;
+
+    Ok(added > 0 && known > 1)
+}
+
+pub async fn revoke_session_by_hash(
+    state: Arc<AppState>,
+    key: &str,
+    key_secondary: &str,
+    session_hash: &str,
+    email: &str,
+) -> Result<bool, AppError> {
+    for session_id in state
+        .redis_connection_manager
+        .clone()
+        .zrange(format!("{}:{}", key_secondary, email), 0, -1)
+        .await?
+    {
+        if get_hash(&session_id) == session_hash {
+            revoke_session(state.clone(), key, key_secondary, &session_id, email).await?;
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+pub async fn migrate_redis_account(
+    state: Arc<AppState>,
+    email: &str,
+    new_email: &str,
+) -> Result<(), AppError> {
+    let mut invocation = MIGRATE_ACCOUNT_SCRIPT.prepare_invoke();
+
+    for redis_action in [
+        RedisAction::ItemFingerprint,
+        RedisAction::SessionStore,
+        RedisAction::LockedItems,
+        RedisAction::SessionDevices,
+        RedisAction::SessionFingerprints,
+        RedisAction::RememberedSessions,
+        RedisAction::KnownDevices,
+    ] {
+        invocation
+            .key(format!("{}:{}", redis_action.as_ref(), email))
+            .key(format!("{}:{}", redis_action.as_ref(), new_email));
+    }
+
+    let _count: () = invocation
+        .arg(new_email)
+        .arg(RedisAction::DeletedItem.as_ref())
+        .arg(RedisAction::Session.as_ref())
+        .invoke_async(&mut state.redis_connection_manager.clone())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_owned_item_ids(state: Arc<AppState>, email: &str) -> Result<Vec<Uuid>, AppError> {
+    Ok(state
+        .redis_connection_manager
+        .clone()
+        .hkeys(format!(
+            "{}:{}",
+            RedisAction::ItemFingerprint.as_ref(),
+            email
+        ))
+        .await?
+        .iter()
+        .filter_map(|item_id| Uuid::parse_str(item_id).ok())
+        .collect())
+}
+
+pub async fn delete_redis_account(
+    state: Arc<AppState>,
+    email: &str,
+    item_ids: &[Uuid],
+) -> Result<(), AppError> {
+    delete_all_sessions(
+        state.clone(),
+        RedisAction::Session.as_ref(),
+        RedisAction::SessionStore.as_ref(),
+        email,
+    )
+    .await?;
+
+    let mut connection = state.redis_connection_manager.clone();
+    let mut pipe = redis::pipe();
+
+    for item_id in item_ids {
+        pipe.del(format!("{}:{}", RedisAction::DeletedItem.as_ref(), item_id))
+            .ignore();
+    }
+
+    for lock_key in connection
+        .smembers(format!("{}:{}", RedisAction::LockKeys.as_ref(), email))
+        .await?
+    {
+        pipe.del(lock_key).ignore();
+    }
+
+    for redis_action in [
+        RedisAction::ItemFingerprint,
+        RedisAction::LockedItems,
+        RedisAction::LockedTime,
+        RedisAction::LockKeys,
+        RedisAction::LockedAuthAccount,
+        RedisAction::LockedVerifyAccount,
+        RedisAction::AccountDeletion,
+        RedisAction::EmailChange,
+        RedisAction::PasskeyRegistration,
+        RedisAction::TotpPending,
+        RedisAction::KnownDevices,
+    ] {
+        pipe.del(format!("{}:{}", redis_action.as_ref(), email))
+            .ignore();
+    }
+
+    pipe.query_async::<()>(&mut connection).await?;
+
+    Ok(())
+}
+
 pub async fn insert_id(
     state: Arc<AppState>,
     key_prefix: &str,
@@ -144,6 +626,23 @@ pub async fn insert_id(
     Ok(())
 }
 
+pub async fn update_id(
+    state: Arc<AppState>,
+    key_prefix: &str,
+    key_id: &str,
+    value: &str,
+) -> Result<(), AppError> {
+    let _: Option<String> = redis::cmd("SET")
+        .arg(format!("{}:{}", key_prefix, key_id))
+        .arg(value)
+        .arg("XX")
+        .arg("KEEPTTL")
+        .query_async(&mut state.redis_connection_manager.clone())
+        .await?;
+
+    Ok(())
+}
+
 pub async fn remove_id(
     state: Arc<AppState>,
     key_prefix: &str,
@@ -188,6 +687,19 @@ pub async fn try_get(
         .await?)
 }
 
+pub fn matches_temporary_code(
+    code_key: &str,
+    magic_link_key: &str,
+    redis_action: &RedisAction,
+    id: &str,
+    stored_code: &str,
+    code: &str,
+) -> bool {
+    verify_code(code_key, code, stored_code)
+        || (*redis_action == RedisAction::Auth
+            && verify_magic_token(magic_link_key, id, stored_code, code))
+}
+
 pub async fn get_redis_account(
     state: Arc<AppState>,
     result: &Option<String>,
@@ -203,7 +715,7 @@ pub async fn get_redis_account(
                 return Ok(None);
             }
 
-            let deserialized: RedisAccount = serde_json::from_str(serialized)?;
+            let mut deserialized: RedisAccount = serde_json::from_str(serialized)?;
 
             if is_redis_locked(
                 state.clone(),
@@ -217,18 +729,33 @@ pub async fn get_redis_account(
             }
 
             let locked = match redis_action {
-                RedisAction::Auth => {
-                    check_locks(
-                        state.clone(),
-                        &deserialized.email,
-                        deserialized.issued_timestamp.expect("auth account"),
-                    )
-                    .await?
-                }
+                RedisAction::Auth => match deserialized.issued_timestamp {
+                    Some(issued_timestamp) => {
+                        check_locks(state.clone(), &deserialized.email, issued_timestamp).await?
+                    }
+                    None => true,
+                },
                 _ => false,
             };
 
-            if !locked && *redis_action != RedisAction::Update && code != deserialized.code {
+            let mut valid_code = matches_temporary_code(
+                &state.config.code_key,
+                &state.config.magic_link_key,
+                redis_action,
+                id,
+                &deserialized.code,
+                code,
+            ) || (*redis_action == RedisAction::Auth
+                && verify_user_totp(state.clone(), &deserialized.email, code).await?);
+
+            let recovery_code_hash = match !valid_code && *redis_action == RedisAction::Auth {
+                true => find_recovery_code(state.clone(), &deserialized.email, code).await?,
+                false => None,
+            };
+
+            valid_code |= recovery_code_hash.is_some();
+
+            if !locked && *redis_action != RedisAction::Update && !valid_code {
                 increment_lock_key(
                     state.clone(),
                     failed_verify_key,
@@ -237,6 +764,21 @@ pub async fn get_redis_account(
                     &state.config.verify_max_attempts,
                 )
                 .await?;
+
+                deserialized.attempts += 1;
+
+                if deserialized.attempts >= state.config.verify_max_attempts {
+                    remove_id(state.clone(), redis_action.as_ref(), id).await?;
+                } else {
+                    update_id(
+                        state.clone(),
+                        redis_action.as_ref(),
+                        id,
+                        &serde_json::to_string(&deserialized)?,
+                    )
+                    .await?;
+                }
+
                 return Ok(None);
             }
 
@@ -246,6 +788,14 @@ pub async fn get_redis_account(
                 return Ok(None);
             }
 
+            // Recovery codes are single use, so only consume one once every
+            // lock check has passed and the request is going to succeed.
+            if let Some(code_hash) = recovery_code_hash
+                && !remove_recovery_code(state.clone(), &deserialized.email, &code_hash).await?
+            {
+                return Ok(None);
+            }
+
             Ok(Some(deserialized))
         }
         None => Ok(None),
@@ -267,7 +817,7 @@ pub async fn create_redis_account(
 
             let password_owned = password.to_owned();
 
-            let password_hash = spawn_blocking(move || hash_password(&password_owned)).await??;
+            let password_hash = spawn_hash_password(password_owned).await?;
 
             Ok(Some(RedisAccount {
                 email: email.to_string(),
@@ -275,6 +825,8 @@ pub async fn create_redis_account(
                 code: generate_code().clone(),
                 issued_timestamp: Some(Utc::now().timestamp_millis()),
                 password_hash: Some(password_hash),
+                remember: false,
+                attempts: 0,
             }))
         }
         Some((hash, locked)) => {
@@ -287,7 +839,7 @@ pub async fn create_redis_account(
             }
 
             if action == Action::Login
-                && !spawn_blocking(move || verify_password(&plaintext, &hash)).await??
+                && !spawn_verify_password(plaintext.clone(), hash.clone()).await?
             {
                 increment_lock_key(
                     state.clone(),
@@ -300,17 +852,36 @@ pub async fn create_redis_account(
                 return Ok(None);
             }
 
+            if action == Action::Login && needs_rehash(&hash) {
+                spawn_rehash_task(state.clone(), email.to_string(), plaintext);
+            }
+
             Ok(Some(RedisAccount {
                 email: email.to_string(),
                 action: action.clone(),
                 code: generate_code().clone(),
                 issued_timestamp: Some(Utc::now().timestamp_millis()),
                 password_hash: None,
+                remember: false,
+                attempts: 0,
             }))
         }
     }
 }
 
+pub fn spawn_rehash_task(state: Arc<AppState>, email: String, password: String) {
+    tokio::spawn(async move {
+        match spawn_hash_password(password).await {
+            Ok(password_hash) => {
+                if let Err(e) = update_password(state.clone(), &email, &password_hash).await {
+                    warn!("Failed to store rehashed password: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to rehash password: {}", e),
+        }
+    });
+}
+
 pub async fn increment_lock_key(
     state: Arc<AppState>,
     key: &str,
@@ -320,6 +891,24 @@ pub async fn increment_lock_key(
 ) -> Result<(), AppError> {
     let _count: () = FAILED_ATTEMPTS_SCRIPT
         .key(format!("{}:{}", key, email))
+        .key(format!("{}:{}", RedisAction::LockKeys.as_ref(), email))
+        .arg(locked_duration_seconds)
+        .arg(max_attempts)
+        .invoke_async(&mut state.redis_connection_manager.clone())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn increment_ip_lock_key(
+    state: Arc<AppState>,
+    key: &str,
+    hashed_ip: &str,
+    locked_duration_seconds: &u16,
+    max_attempts: &u8,
+) -> Result<(), AppError> {
+    let _count: () = FAILED_ATTEMPTS_SCRIPT
+        .key(format!("{}:{}", key, hashed_ip))
         .arg(locked_duration_seconds)
         .arg(max_attempts)
         .invoke_async(&mut state.redis_connection_manager.clone())
@@ -345,13 +934,26 @@ pub async fn handle_item_insertion(
     state: Arc<AppState>,
     item: ItemPayload,
     email: &str,
-) -> Result<(), AppError> {
+) -> Result<Uuid, AppError> {
+    let fingerprint = get_fingerprint(&item.title, &item.description);
+    let mapping_ttl = (get_expiration_days(state.clone(), &item.item_type) * 86400 * 2) as u32;
+    let item_id = insert_item(state.clone(), item).await?;
+
     insert_id(
         state.clone(),
         RedisAction::DeletedItem.as_ref(),
-        &insert_item(state.clone(), item).await?.to_string(),
+        &item_id.to_string(),
         email,
-        1_209_600,
+        mapping_ttl,
+    )
+    .await?;
+
+    insert_item_fingerprint(
+        state.clone(),
+        email,
+        &item_id.to_string(),
+        &fingerprint,
+        mapping_ttl,
     )
     .await?;
 
@@ -364,6 +966,105 @@ pub async fn handle_item_insertion(
     )
     .await?;
 
+    Ok(item_id)
+}
+
+pub async fn insert_item_fingerprint(
+    state: Arc<AppState>,
+    email: &str,
+    item_id: &str,
+    fingerprint: &str,
+    ttl: u32,
+) -> Result<(), AppError> {
+    let key = format!("{}:{}", RedisAction::ItemFingerprint.as_ref(), email);
+
+    let mut pipe = redis::pipe();
+
+    pipe.hset(&key, item_id, fingerprint).ignore();
+    pipe.expire(&key, ttl.into()).ignore();
+
+    pipe.query_async::<()>(&mut state.redis_connection_manager.clone())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_item_fingerprints(
+    state: Arc<AppState>,
+    email: &str,
+) -> Result<Vec<String>, AppError> {
+    Ok(state
+        .redis_connection_manager
+        .clone()
+        .hvals(format!(
+            "{}:{}",
+            RedisAction::ItemFingerprint.as_ref(),
+            email
+        ))
+        .await?)
+}
+
+pub async fn remove_item_fingerprint(
+    state: Arc<AppState>,
+    email: &str,
+    item_id: &str,
+) -> Result<(), AppError> {
+    state
+        .redis_connection_manager
+        .clone()
+        .hdel(
+            format!("{}:{}", RedisAction::ItemFingerprint.as_ref(), email),
+            item_id,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn handle_draft_publication(
+    state: Arc<AppState>,
+    email: &str,
+    draft_id: Uuid,
+) -> Result<bool, AppError> {
+    let draft = match get_draft(state.clone(), email, draft_id).await? {
+        Some(draft) => draft,
+        None => return Ok(false),
+    };
+
+    if is_redis_locked(
+        state.clone(),
+        RedisAction::LockedItems.as_ref(),
+        email,
+        &state.config.max_items,
+    )
+    .await?
+    {
+        return Ok(false);
+    }
+
+    delete_draft(state.clone(), email, draft_id).await?;
+
+    handle_item_insertion(state.clone(), draft.item, email).await?;
+
+    Ok(true)
+}
+
+pub async fn get_publish_cursor(state: Arc<AppState>) -> Result<Option<i64>, AppError> {
+    Ok(state
+        .redis_connection_manager
+        .clone()
+        .get(RedisAction::PublishCursor.as_ref())
+        .await?
+        .and_then(|cursor| cursor.parse().ok()))
+}
+
+pub async fn set_publish_cursor(state: Arc<AppState>, bucket: i64) -> Result<(), AppError> {
+    state
+        .redis_connection_manager
+        .clone()
+        .set(RedisAction::PublishCursor.as_ref(), bucket)
+        .await?;
+
     Ok(())
 }
 
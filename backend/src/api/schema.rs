@@ -3,6 +3,13 @@ pub const KEYSPACE: &str = "boiler_swap";
 pub mod tables {
     pub const USERS: &str = "users";
     pub const ITEMS: &str = "items";
+    pub const DRAFTS: &str = "drafts";
+    pub const SCHEDULED_DRAFTS: &str = "scheduled_drafts";
+    pub const QUESTIONS: &str = "questions";
+    pub const FOUND_ITEMS: &str = "found_items";
+    pub const PASSKEYS: &str = "passkeys";
+    pub const AUDIT_LOG: &str = "audit_log";
+    pub const RECOVERY_CODES: &str = "recovery_codes";
     pub const CDC: &str = "cdc";
 }
 
@@ -17,6 +24,12 @@ pub mod columns {
         pub const LOCKED: &str = "locked";
         pub const LOCKED_TYPE: &str = "boolean";
 
+        pub const TOTP_SECRET: &str = "totp_secret";
+        pub const TOTP_SECRET_TYPE: &str = "text";
+
+        pub const MUTE_NOTICES: &str = "mute_notices";
+        pub const MUTE_NOTICES_TYPE: &str = "boolean";
+
         pub const PRIMARY_KEY: &str = EMAIL;
         pub const TTL: &str = "126144000";
     }
@@ -46,6 +59,160 @@ pub mod columns {
         pub const EXPIRATION_DATE: &str = "expiration_date";
         pub const EXPIRATION_DATE_TYPE: &str = "date";
 
+        pub const ISBN: &str = "isbn";
+        pub const ISBN_TYPE: &str = "text";
+
+        pub const AUTHOR: &str = "author";
+        pub const AUTHOR_TYPE: &str = "text";
+
+        pub const COURSE_CODE: &str = "course_code";
+        pub const COURSE_CODE_TYPE: &str = "text";
+
         pub const PRIMARY_KEY: &str = ITEM_ID;
     }
+
+    pub mod drafts {
+        pub const EMAIL: &str = "email";
+        pub const EMAIL_TYPE: &str = "text";
+
+        pub const DRAFT_ID: &str = "draft_id";
+        pub const DRAFT_ID_TYPE: &str = "uuid";
+
+        pub const ITEM_TYPE: &str = "item_type";
+        pub const ITEM_TYPE_TYPE: &str = "tinyint";
+
+        pub const TITLE: &str = "title";
+        pub const TITLE_TYPE: &str = "text";
+
+        pub const CONDITION: &str = "condition";
+        pub const CONDITION_TYPE: &str = "tinyint";
+
+        pub const LOCATION: &str = "location";
+        pub const LOCATION_TYPE: &str = "tinyint";
+
+        pub const DESCRIPTION: &str = "description";
+        pub const DESCRIPTION_TYPE: &str = "text";
+
+        pub const EMOJI: &str = "emoji";
+        pub const EMOJI_TYPE: &str = "tinyint";
+
+        pub const PUBLISH_AT: &str = "publish_at";
+        pub const PUBLISH_AT_TYPE: &str = "timestamp";
+
+        pub const ISBN: &str = "isbn";
+        pub const ISBN_TYPE: &str = "text";
+
+        pub const COURSE_CODE: &str = "course_code";
+        pub const COURSE_CODE_TYPE: &str = "text";
+
+        pub const BLOCKED: &str = "blocked";
+        pub const BLOCKED_TYPE: &str = "boolean";
+
+        pub const PARTITION_KEY: &str = EMAIL;
+        pub const CLUSTERING_KEY: &str = DRAFT_ID;
+        pub const TTL: &str = "2592000";
+    }
+
+    pub mod scheduled_drafts {
+        pub const PUBLISH_BUCKET: &str = "publish_bucket";
+        pub const PUBLISH_BUCKET_TYPE: &str = "bigint";
+
+        pub const PUBLISH_AT: &str = "publish_at";
+        pub const PUBLISH_AT_TYPE: &str = "timestamp";
+
+        pub const EMAIL: &str = "email";
+        pub const EMAIL_TYPE: &str = "text";
+
+        pub const DRAFT_ID: &str = "draft_id";
+        pub const DRAFT_ID_TYPE: &str = "uuid";
+
+        pub const PARTITION_KEY: &str = PUBLISH_BUCKET;
+        pub const BUCKET_SECONDS: i64 = 3600;
+        pub const TTL: &str = "2592000";
+    }
+
+    pub mod questions {
+        pub const ITEM_ID: &str = "item_id";
+        pub const ITEM_ID_TYPE: &str = "uuid";
+
+        pub const QUESTION_ID: &str = "question_id";
+        pub const QUESTION_ID_TYPE: &str = "uuid";
+
+        pub const EMAIL: &str = "email";
+        pub const EMAIL_TYPE: &str = "text";
+
+        pub const QUESTION: &str = "question";
+        pub const QUESTION_TYPE: &str = "text";
+
+        pub const ANSWER: &str = "answer";
+        pub const ANSWER_TYPE: &str = "text";
+
+        pub const ASKED_AT: &str = "asked_at";
+        pub const ASKED_AT_TYPE: &str = "timestamp";
+
+        pub const PARTITION_KEY: &str = ITEM_ID;
+        pub const CLUSTERING_KEY: &str = QUESTION_ID;
+        pub const TTL: &str = "1814400";
+    }
+
+    pub mod found_items {
+        pub const ITEM_ID: &str = "item_id";
+        pub const ITEM_ID_TYPE: &str = "uuid";
+
+        pub const QUESTIONS: &str = "questions";
+        pub const QUESTIONS_TYPE: &str = "list<text>";
+
+        pub const ANSWER_HASHES: &str = "answer_hashes";
+        pub const ANSWER_HASHES_TYPE: &str = "list<text>";
+
+        pub const PRIMARY_KEY: &str = ITEM_ID;
+    }
+
+    pub mod passkeys {
+        pub const EMAIL: &str = "email";
+        pub const EMAIL_TYPE: &str = "text";
+
+        pub const CREDENTIAL_ID: &str = "credential_id";
+        pub const CREDENTIAL_ID_TYPE: &str = "text";
+
+        pub const PASSKEY: &str = "passkey";
+        pub const PASSKEY_TYPE: &str = "text";
+
+        pub const CREATED_AT: &str = "created_at";
+        pub const CREATED_AT_TYPE: &str = "timestamp";
+
+        pub const PARTITION_KEY: &str = EMAIL;
+        pub const CLUSTERING_KEY: &str = CREDENTIAL_ID;
+        pub const TTL: &str = "126144000";
+    }
+
+    pub mod audit_log {
+        pub const SUBJECT: &str = "subject";
+        pub const SUBJECT_TYPE: &str = "text";
+
+        pub const EVENT_ID: &str = "event_id";
+        pub const EVENT_ID_TYPE: &str = "uuid";
+
+        pub const EVENT: &str = "event";
+        pub const EVENT_TYPE: &str = "text";
+
+        pub const CREATED_AT: &str = "created_at";
+        pub const CREATED_AT_TYPE: &str = "timestamp";
+
+        pub const PARTITION_KEY: &str = SUBJECT;
+        pub const CLUSTERING_KEY: &str = EVENT_ID;
+        pub const TTL: &str = "31536000";
+    }
+
+    pub mod recovery_codes {
+        pub const EMAIL: &str = "email";
+        pub const EMAIL_TYPE: &str = "text";
+
+        pub const CODE_HASH: &str = "code_hash";
+        pub const CODE_HASH_TYPE: &str = "text";
+
+        pub const PARTITION_KEY: &str = EMAIL;
+        pub const CLUSTERING_KEY: &str = CODE_HASH;
+        pub const TTL: &str = "126144000";
+    }
 }
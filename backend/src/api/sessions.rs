@@ -1,8 +1,12 @@
 use super::{
+    csrf::{CSRF_COOKIE, get_csrf_token},
     database::insert_user,
+    magiclink::get_magic_link,
     models::{Action, RedisAccount, RedisAction},
-    redis::{increment_lock_key, insert_id, insert_session},
-    twofactor::spawn_code_task,
+    notifications::{SecurityNotice, spawn_notice_task},
+    redis::{increment_lock_key, insert_id, insert_session, is_redis_locked, remember_device},
+    twofactor::{generate_code, hash_code, spawn_code_task},
+    utilities::{get_hash, get_hashed_ip, get_key, get_session_fingerprint},
 };
 use crate::{AppError, AppState};
 use axum::http::{
@@ -10,17 +14,20 @@ use axum::http::{
     header::{HeaderMap, SET_COOKIE},
 };
 use axum_extra::extract::CookieJar;
+use chrono::Utc;
 use cookie::{Cookie, CookieJar as cookieCookieJar, SameSite::Strict, time::Duration};
 use once_cell::sync::Lazy;
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc};
 use uuid::Uuid;
 
-static COOKIES_TO_CLEAR: Lazy<Vec<&'static str>> = Lazy::new(|| {
+pub static COOKIES_TO_CLEAR: Lazy<Vec<&'static str>> = Lazy::new(|| {
     vec![
         RedisAction::Session.as_ref(),
         RedisAction::Forgot.as_ref(),
         RedisAction::Update.as_ref(),
         RedisAction::Auth.as_ref(),
+        RedisAction::PasskeyAuthentication.as_ref(),
+        RedisAction::OidcAuthentication.as_ref(),
     ]
 });
 
@@ -37,10 +44,18 @@ pub static CLEARED_COOKIES: Lazy<cookieCookieJar> = Lazy::new(|| {
         jar.add(expired);
     }
 
+    let expired_csrf = Cookie::build(CSRF_COOKIE)
+        .path("/")
+        .http_only(false)
+        .secure(true)
+        .same_site(Strict)
+        .max_age(Duration::seconds(0));
+    jar.add(expired_csrf);
+
     jar
 });
 
-pub fn generate_cookie(key: &str, value: &str, ttl: i64) -> HeaderMap {
+fn get_cookie_jar(key: &str, value: &str, ttl: i64) -> cookieCookieJar {
     let mut jar = CLEARED_COOKIES.clone();
 
     let new_cookie = Cookie::build((key.to_owned(), value.to_owned()))
@@ -52,6 +67,10 @@ pub fn generate_cookie(key: &str, value: &str, ttl: i64) -> HeaderMap {
 
     jar.add(new_cookie);
 
+    jar
+}
+
+fn get_cookie_headers(jar: &cookieCookieJar) -> HeaderMap {
     let mut headers = HeaderMap::new();
 
     for cookie in jar.delta() {
@@ -64,6 +83,40 @@ pub fn generate_cookie(key: &str, value: &str, ttl: i64) -> HeaderMap {
     headers
 }
 
+pub fn generate_cookie(key: &str, value: &str, ttl: i64) -> HeaderMap {
+    get_cookie_headers(&get_cookie_jar(key, value, ttl))
+}
+
+pub fn generate_expired_cookie(key: &str) -> HeaderMap {
+    let mut jar = cookieCookieJar::new();
+
+    jar.add(
+        Cookie::build(key.to_owned())
+            .path("/")
+            .http_only(true)
+            .secure(true)
+            .same_site(Strict)
+            .max_age(Duration::seconds(0)),
+    );
+
+    get_cookie_headers(&jar)
+}
+
+pub fn generate_session_cookie(csrf_key: &str, key: &str, session_id: &str, ttl: i64) -> HeaderMap {
+    let mut jar = get_cookie_jar(key, session_id, ttl);
+
+    let csrf_cookie = Cookie::build((CSRF_COOKIE, get_csrf_token(csrf_key, session_id)))
+        .path("/")
+        .http_only(false)
+        .secure(true)
+        .same_site(Strict)
+        .max_age(Duration::seconds(ttl));
+
+    jar.add(csrf_cookie);
+
+    get_cookie_headers(&jar)
+}
+
 pub fn get_cookie(headers: &HeaderMap, key: &str) -> Option<String> {
     CookieJar::from_headers(headers)
         .get(key)
@@ -78,12 +131,22 @@ pub async fn create_temporary_session(
     forgot_key: &Option<String>,
     code_key: &Option<String>,
 ) -> Result<HeaderMap, AppError> {
+    let id = Uuid::new_v4().to_string();
+
+    let mut stored_account = redis_account.clone();
+
     if redis_action != RedisAction::Update {
+        stored_account.code = hash_code(&state.config.code_key, &redis_account.code);
+
+        let link = (redis_action == RedisAction::Auth)
+            .then(|| get_magic_link(&state, &id, &stored_account.code));
+
         spawn_code_task(
             state.clone(),
             redis_account.email.clone(),
             redis_account.code.clone(),
             forgot_key.clone(),
+            link,
         );
 
         increment_lock_key(
@@ -98,11 +161,9 @@ pub async fn create_temporary_session(
 
     let serialized = match result {
         Some(result) => result,
-        None => &serde_json::to_string(&redis_account)?,
+        None => &serde_json::to_string(&stored_account)?,
     };
 
-    let id = Uuid::new_v4().to_string();
-
     insert_id(
         state.clone(),
         redis_action.as_ref(),
@@ -119,17 +180,56 @@ pub async fn create_temporary_session(
     ))
 }
 
+pub async fn create_session_challenge(
+    state: Arc<AppState>,
+    email: &str,
+    headers: &HeaderMap,
+    direct_ip: IpAddr,
+) -> Result<HeaderMap, AppError> {
+    let code_key = get_key(
+        RedisAction::LockedCode,
+        &get_hashed_ip(&state.config, headers, direct_ip),
+    );
+
+    if is_redis_locked(state.clone(), &code_key, email, &state.config.max_codes).await? {
+        return Ok(generate_cookie(RedisAction::Session.as_ref(), "", 0));
+    }
+
+    let redis_account = RedisAccount {
+        email: email.to_string(),
+        action: Action::Login,
+        code: generate_code(),
+        issued_timestamp: Some(Utc::now().timestamp_millis()),
+        password_hash: None,
+        remember: false,
+        attempts: 0,
+    };
+
+    create_temporary_session(
+        state.clone(),
+        &None,
+        &redis_account,
+        RedisAction::Auth,
+        &None,
+        &Some(code_key),
+    )
+    .await
+}
+
 pub async fn create_session(
     state: Arc<AppState>,
     redis_account: &RedisAccount,
     redis_action: RedisAction,
     redis_action_secondary: RedisAction,
+    headers: &HeaderMap,
+    direct_ip: IpAddr,
 ) -> Result<HeaderMap, AppError> {
     if redis_account.action == Action::Signup {
         insert_user(state.clone(), redis_account.clone()).await?;
     }
 
     let session_id = Uuid::new_v4().to_string();
+    let fingerprint = get_session_fingerprint(&state.config, headers, direct_ip);
 
     insert_session(
         state.clone(),
@@ -137,12 +237,35 @@ pub async fn create_session(
         &session_id,
         redis_action_secondary.as_ref(),
         &redis_account.email,
+        &fingerprint,
+        redis_account.remember,
     )
     .await?;
 
-    Ok(generate_cookie(
+    if remember_device(
+        state.clone(),
+        &redis_account.email,
+        &get_hash(&fingerprint.device),
+    )
+    .await?
+    {
+        spawn_notice_task(
+            state.clone(),
+            redis_account.email.clone(),
+            SecurityNotice::NewSignIn,
+            fingerprint.device,
+        );
+    }
+
+    let ttl = match redis_account.remember {
+        true => state.config.remember_session_max_duration_seconds,
+        false => state.config.session_max_duration_seconds,
+    };
+
+    Ok(generate_session_cookie(
+        &state.config.csrf_key,
         redis_action.as_ref(),
         &session_id,
-        state.config.session_duration_seconds.into(),
+        ttl.into(),
     ))
 }
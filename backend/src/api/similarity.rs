@@ -0,0 +1,126 @@
+use super::{
+    meilisearch::search_items,
+    models::{ItemPayload, RedisAction},
+    redis::{get_item_fingerprints, try_get},
+    schema::tables,
+};
+use crate::{AppError, AppState};
+use std::{collections::HashMap, sync::Arc};
+
+pub fn normalize_text(payload: &str) -> String {
+    payload
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn get_fingerprint(title: &str, description: &str) -> String {
+    normalize_text(&format!("{} {}", title, description))
+}
+
+fn get_bigrams(payload: &str) -> HashMap<(char, char), usize> {
+    let chars: Vec<char> = payload.chars().collect();
+    let mut bigrams = HashMap::new();
+
+    for pair in chars.windows(2) {
+        *bigrams.entry((pair[0], pair[1])).or_insert(0) += 1;
+    }
+
+    bigrams
+}
+
+pub fn get_similarity(first: &str, second: &str) -> f64 {
+    if first == second {
+        return 1.0;
+    }
+
+    let first_bigrams = get_bigrams(first);
+    let second_bigrams = get_bigrams(second);
+
+    let total: usize =
+        first_bigrams.values().sum::<usize>() + second_bigrams.values().sum::<usize>();
+
+    if total == 0 {
+        return 0.0;
+    }
+
+    let shared: usize = first_bigrams
+        .iter()
+        .map(|(bigram, count)| (*count).min(*second_bigrams.get(bigram).unwrap_or(&0)))
+        .sum();
+
+    (2 * shared) as f64 / total as f64
+}
+
+pub fn is_similar(first: &str, second: &str, threshold_percent: &u8) -> bool {
+    get_similarity(first, second) * 100.0 >= f64::from(*threshold_percent)
+}
+
+pub async fn is_duplicate_item(
+    state: Arc<AppState>,
+    email: &str,
+    item: &ItemPayload,
+) -> Result<bool, AppError> {
+    let fingerprint = get_fingerprint(&item.title, &item.description);
+
+    if get_item_fingerprints(state.clone(), email)
+        .await?
+        .iter()
+        .any(|existing| {
+            is_similar(
+                &fingerprint,
+                existing,
+                &state.config.duplicate_similarity_percent,
+            )
+        })
+    {
+        return Ok(true);
+    }
+
+    for hit in search_items(state.meili_client.clone(), tables::ITEMS, &item.title).await? {
+        if is_similar(
+            &fingerprint,
+            &get_fingerprint(&hit.title, &hit.description),
+            &state.config.duplicate_similarity_percent,
+        ) && try_get(
+            state.clone(),
+            RedisAction::DeletedItem.as_ref(),
+            &hit.item_id.to_string(),
+        )
+        .await?
+        .as_deref()
+            == Some(email)
+        {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_punctuation_and_case() {
+        assert_eq!(
+            normalize_text("  Calculus: Early-Transcendentals! "),
+            "calculus early transcendentals"
+        );
+    }
+
+    #[test]
+    fn detects_near_duplicates() {
+        let first = get_fingerprint("Calculus textbook", "Barely used, 8th edition");
+        let second = get_fingerprint("calculus Textbook", "barely used 8th edition!");
+        let third = get_fingerprint("Mini fridge", "Works great, pick up at Cary");
+
+        assert!(is_similar(&first, &second, &90));
+        assert!(!is_similar(&first, &third, &50));
+    }
+}
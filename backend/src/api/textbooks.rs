@@ -0,0 +1,270 @@
+use super::models::{ItemPayload, ItemType};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+pub struct Textbook {
+    pub title: String,
+    pub author: String,
+}
+
+pub static COURSE_CODE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Z]{2,5}) ?(\d{3,5})$").unwrap());
+pub static TEXTBOOKS: Lazy<HashMap<String, Textbook>> = Lazy::new(|| {
+    include_str!("../../data/textbooks.tsv")
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some((
+                fields.next()?.to_string(),
+                Textbook {
+                    title: fields.next()?.to_string(),
+                    author: fields.next()?.to_string(),
+                },
+            ))
+        })
+        .collect()
+});
+
+fn get_digits(isbn: &str) -> Vec<u32> {
+    isbn.chars()
+        .map(|c| match c {
+            'X' => 10,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .collect()
+}
+
+fn get_isbn13_check_digit(digits: &[u32]) -> u32 {
+    let sum: u32 = digits
+        .iter()
+        .take(12)
+        .enumerate()
+        .map(|(index, digit)| if index % 2 == 0 { *digit } else { digit * 3 })
+        .sum();
+
+    (10 - sum % 10) % 10
+}
+
+pub fn normalize_isbn(isbn: &str) -> Option<String> {
+    let stripped: String = isbn
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase();
+
+    if !stripped.is_ascii() {
+        return None;
+    }
+
+    match stripped.len() {
+        10 if stripped[..9].chars().all(|c| c.is_ascii_digit())
+            && stripped[9..]
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == 'X') =>
+        {
+            let digits = get_digits(&stripped);
+
+            let sum: u32 = digits
+                .iter()
+                .enumerate()
+                .map(|(index, digit)| (10 - index as u32) * digit)
+                .sum();
+
+            if !sum.is_multiple_of(11) {
+                return None;
+            }
+
+            let prefixed = format!("978{}", &stripped[..9]);
+            let check_digit = get_isbn13_check_digit(&get_digits(&prefixed));
+
+            Some(format!("{}{}", prefixed, check_digit))
+        }
+        13 if stripped.chars().all(|c| c.is_ascii_digit()) => {
+            let digits = get_digits(&stripped);
+
+            if get_isbn13_check_digit(&digits) != digits[12] {
+                return None;
+            }
+
+            Some(stripped)
+        }
+        _ => None,
+    }
+}
+
+pub fn normalize_course_code(course_code: &str) -> Option<String> {
+    let uppercase = course_code.trim().to_uppercase();
+
+    COURSE_CODE_REGEX
+        .captures(&uppercase)
+        .map(|captures| format!("{} {}", &captures[1], &captures[2]))
+}
+
+pub fn get_textbook(isbn: &str) -> Option<&'static Textbook> {
+    TEXTBOOKS.get(isbn)
+}
+
+pub fn enrich_textbook(item: &mut ItemPayload) -> Result<(), &'static str> {
+    if item.isbn.is_none() && item.course_code.is_none() {
+        return Ok(());
+    }
+
+    if !matches!(item.item_type, ItemType::Books) {
+        return Err("Only books can have an ISBN or course code");
+    }
+
+    if let Some(isbn) = &item.isbn {
+        let isbn = normalize_isbn(isbn).ok_or("Invalid ISBN")?;
+
+        if let Some(textbook) = get_textbook(&isbn)
+            && item.title.trim().is_empty()
+        {
+            item.title = textbook.title.clone();
+        }
+
+        item.isbn = Some(isbn);
+    }
+
+    if let Some(course_code) = &item.course_code {
+        item.course_code = Some(normalize_course_code(course_code).ok_or("Invalid course code")?);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::{Condition, Emoji, Location};
+
+    fn get_item(item_type: ItemType, isbn: Option<&str>, course_code: Option<&str>) -> ItemPayload {
+        ItemPayload {
+            item_type,
+            condition: Condition::Excellent,
+            title: String::new(),
+            description: "Barely used".to_string(),
+            location: Location::CaryQuadEast,
+            emoji: Emoji::Chair,
+            isbn: isbn.map(str::to_string),
+            course_code: course_code.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn normalizes_valid_isbn13() {
+        assert_eq!(
+            normalize_isbn("978-0-262-03384-8").as_deref(),
+            Some("9780262033848")
+        );
+        assert_eq!(
+            normalize_isbn(" 978 0262033848 ").as_deref(),
+            Some("9780262033848")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_isbn13_checksum() {
+        assert_eq!(normalize_isbn("9780262033847"), None);
+        assert_eq!(normalize_isbn("978026203384X"), None);
+    }
+
+    #[test]
+    fn converts_isbn10_to_isbn13() {
+        assert_eq!(
+            normalize_isbn("0-262-03384-4").as_deref(),
+            Some("9780262033848")
+        );
+    }
+
+    #[test]
+    fn accepts_isbn10_with_x_check_digit() {
+        assert_eq!(
+            normalize_isbn("0-8044-2957-X").as_deref(),
+            Some("9780804429573")
+        );
+        assert_eq!(
+            normalize_isbn("080442957x").as_deref(),
+            Some("9780804429573")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_isbn10() {
+        assert_eq!(normalize_isbn("0262033845"), None);
+        assert_eq!(normalize_isbn("0804429570"), None);
+        assert_eq!(normalize_isbn("08044295X7"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_isbn() {
+        assert_eq!(normalize_isbn(""), None);
+        assert_eq!(normalize_isbn("026203384"), None);
+        assert_eq!(normalize_isbn("97802620338480"), None);
+        assert_eq!(normalize_isbn("978026203384٨"), None);
+    }
+
+    #[test]
+    fn normalizes_course_codes() {
+        assert_eq!(
+            normalize_course_code("MA 26100").as_deref(),
+            Some("MA 26100")
+        );
+        assert_eq!(
+            normalize_course_code("MA26100").as_deref(),
+            Some("MA 26100")
+        );
+        assert_eq!(normalize_course_code(" cs 180 ").as_deref(), Some("CS 180"));
+    }
+
+    #[test]
+    fn rejects_invalid_course_codes() {
+        assert_eq!(normalize_course_code("M 26100"), None);
+        assert_eq!(normalize_course_code("MA  26100"), None);
+        assert_eq!(normalize_course_code("MA 26"), None);
+        assert_eq!(normalize_course_code("MA-26100"), None);
+    }
+
+    #[test]
+    fn enrich_fills_title_and_normalizes_fields() {
+        let mut item = get_item(ItemType::Books, Some("0-262-03384-4"), Some("cs25100"));
+
+        assert!(enrich_textbook(&mut item).is_ok());
+        assert_eq!(item.title, "Introduction to Algorithms, 3rd Edition");
+        assert_eq!(item.isbn.as_deref(), Some("9780262033848"));
+        assert_eq!(item.course_code.as_deref(), Some("CS 25100"));
+    }
+
+    #[test]
+    fn enrich_keeps_user_title() {
+        let mut item = get_item(ItemType::Books, Some("9780262033848"), None);
+        item.title = "CLRS".to_string();
+
+        assert!(enrich_textbook(&mut item).is_ok());
+        assert_eq!(item.title, "CLRS");
+    }
+
+    #[test]
+    fn enrich_rejects_invalid_fields() {
+        let mut item = get_item(ItemType::Books, Some("9780262033847"), None);
+        assert_eq!(enrich_textbook(&mut item), Err("Invalid ISBN"));
+
+        let mut item = get_item(ItemType::Books, None, Some("Calculus"));
+        assert_eq!(enrich_textbook(&mut item), Err("Invalid course code"));
+
+        let mut item = get_item(ItemType::Furniture, Some("9780262033848"), None);
+        assert_eq!(
+            enrich_textbook(&mut item),
+            Err("Only books can have an ISBN or course code")
+        );
+    }
+
+    #[test]
+    fn enrich_ignores_items_without_textbook_fields() {
+        let mut item = get_item(ItemType::Furniture, None, None);
+
+        assert!(enrich_textbook(&mut item).is_ok());
+        assert!(item.title.is_empty());
+    }
+}
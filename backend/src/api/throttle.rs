@@ -0,0 +1,223 @@
+use super::{
+    models::RedisAction,
+    redis::{increment_ip_lock_key, increment_lock_key, is_redis_locked, remove_id, try_get},
+};
+use crate::{AppError, AppState};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+
+#[derive(Clone, Copy)]
+pub enum ThrottleScope {
+    Auth,
+    Verify,
+}
+
+impl ThrottleScope {
+    fn ip_key(self) -> RedisAction {
+        match self {
+            ThrottleScope::Auth => RedisAction::LockedAuthIp,
+            ThrottleScope::Verify => RedisAction::LockedVerifyIp,
+        }
+    }
+
+    fn account_key(self) -> RedisAction {
+        match self {
+            ThrottleScope::Auth => RedisAction::LockedAuthAccount,
+            ThrottleScope::Verify => RedisAction::LockedVerifyAccount,
+        }
+    }
+
+    fn ip_max_attempts(self, state: &AppState) -> u8 {
+        match self {
+            ThrottleScope::Auth => state.config.auth_ip_max_attempts,
+            ThrottleScope::Verify => state.config.verify_ip_max_attempts,
+        }
+    }
+
+    fn account_max_attempts(self, state: &AppState) -> u8 {
+        match self {
+            ThrottleScope::Auth => state.config.auth_account_max_attempts,
+            ThrottleScope::Verify => state.config.verify_account_max_attempts,
+        }
+    }
+
+    fn lock_duration_seconds(self, state: &AppState) -> u16 {
+        match self {
+            ThrottleScope::Auth => state.config.auth_lock_duration_seconds,
+            ThrottleScope::Verify => state.config.verify_lock_duration_seconds,
+        }
+    }
+}
+
+pub async fn is_throttled(
+    state: Arc<AppState>,
+    scope: ThrottleScope,
+    hashed_ip: &str,
+    email: &str,
+) -> Result<bool, AppError> {
+    Ok(is_ip_throttled(state.clone(), scope, hashed_ip).await?
+        || is_redis_locked(
+            state.clone(),
+            scope.account_key().as_ref(),
+            email,
+            &scope.account_max_attempts(&state),
+        )
+        .await?)
+}
+
+pub async fn is_ip_throttled(
+    state: Arc<AppState>,
+    scope: ThrottleScope,
+    hashed_ip: &str,
+) -> Result<bool, AppError> {
+    is_redis_locked(
+        state.clone(),
+        scope.ip_key().as_ref(),
+        hashed_ip,
+        &scope.ip_max_attempts(&state),
+    )
+    .await
+}
+
+pub async fn record_ip_failure(
+    state: Arc<AppState>,
+    scope: ThrottleScope,
+    hashed_ip: &str,
+) -> Result<(), AppError> {
+    increment_ip_lock_key(
+        state.clone(),
+        scope.ip_key().as_ref(),
+        hashed_ip,
+        &scope.lock_duration_seconds(&state),
+        &scope.ip_max_attempts(&state),
+    )
+    .await
+}
+
+pub async fn record_failure(
+    state: Arc<AppState>,
+    scope: ThrottleScope,
+    hashed_ip: &str,
+    email: &str,
+) -> Result<(), AppError> {
+    record_ip_failure(state.clone(), scope, hashed_ip).await?;
+
+    increment_lock_key(
+        state.clone(),
+        scope.account_key().as_ref(),
+        email,
+        &scope.lock_duration_seconds(&state),
+        &scope.account_max_attempts(&state),
+    )
+    .await
+}
+
+fn get_failure_keys<'a>(
+    scope: ThrottleScope,
+    hashed_ip: &'a str,
+    email: &'a str,
+) -> [(RedisAction, &'a str); 2] {
+    [(scope.ip_key(), hashed_ip), (scope.account_key(), email)]
+}
+
+// The per-IP counter is left to expire on its own so a successful login
+// with a throwaway account cannot reset it between guesses.
+fn get_cleared_keys(scope: ThrottleScope, email: &str) -> [(RedisAction, &str); 1] {
+    [(scope.account_key(), email)]
+}
+
+pub async fn clear_failures(
+    state: Arc<AppState>,
+    scope: ThrottleScope,
+    email: &str,
+) -> Result<(), AppError> {
+    for (key, id) in get_cleared_keys(scope, email) {
+        remove_id(state.clone(), key.as_ref(), id).await?;
+    }
+
+    Ok(())
+}
+
+pub fn get_retry_message(state: &AppState, scope: ThrottleScope) -> String {
+    format_retry_message(scope.lock_duration_seconds(state))
+}
+
+fn format_retry_message(lock_duration_seconds: u16) -> String {
+    match lock_duration_seconds.div_ceil(60) {
+        1 => "Try again in 1 minute".to_string(),
+        minutes => format!("Try again in {} minutes", minutes),
+    }
+}
+
+pub async fn slow_down(
+    state: Arc<AppState>,
+    scope: ThrottleScope,
+    hashed_ip: &str,
+    email: &str,
+) -> Result<(), AppError> {
+    let mut attempts = 0u32;
+
+    for (key, id) in get_failure_keys(scope, hashed_ip, email) {
+        if let Some(count) = try_get(state.clone(), key.as_ref(), id).await? {
+            attempts = attempts.max(count.parse()?);
+        }
+    }
+
+    let after_attempts = state.config.slow_down_after_attempts as u32;
+
+    if attempts <= after_attempts {
+        return Ok(());
+    }
+
+    let exponent = (attempts - after_attempts - 1).min(16);
+    let delay_millis = (state.config.slow_down_base_millis as u64)
+        .saturating_mul(1 << exponent)
+        .min(state.config.slow_down_max_millis as u64);
+
+    sleep(Duration::from_millis(delay_millis)).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const IP_MAX_ATTEMPTS: u8 = 3;
+
+    #[test]
+    fn success_does_not_reset_ip_counter() {
+        let mut counters: HashMap<(String, String), u8> = HashMap::new();
+        let scope = ThrottleScope::Auth;
+        let hashed_ip = "hashed-ip";
+
+        for round in 0..IP_MAX_ATTEMPTS {
+            let victim = "victim@purdue.edu".to_string();
+            for (key, id) in get_failure_keys(scope, hashed_ip, &victim) {
+                *counters
+                    .entry((key.as_ref().to_string(), id.to_string()))
+                    .or_default() += 1;
+            }
+
+            let throwaway = format!("throwaway{}@purdue.edu", round);
+            for (key, id) in get_cleared_keys(scope, &throwaway) {
+                counters.remove(&(key.as_ref().to_string(), id.to_string()));
+            }
+        }
+
+        let ip_count = counters
+            .get(&(scope.ip_key().as_ref().to_string(), hashed_ip.to_string()))
+            .copied()
+            .unwrap_or_default();
+
+        assert_eq!(ip_count, IP_MAX_ATTEMPTS);
+    }
+
+    #[test]
+    fn retry_message_rounds_up_to_whole_minutes() {
+        assert_eq!(format_retry_message(600), "Try again in 10 minutes");
+        assert_eq!(format_retry_message(601), "Try again in 11 minutes");
+        assert_eq!(format_retry_message(45), "Try again in 1 minute");
+    }
+}
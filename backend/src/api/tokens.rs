@@ -0,0 +1,121 @@
+use super::models::ApiClaims;
+use crate::{AppError, config::Config};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+
+pub const MAX_API_TOKEN_LIFETIME_SECONDS: i64 = 900;
+
+pub struct ApiTokenKey {
+    pub kid: String,
+    pub decoding_key: DecodingKey,
+    pub validation: Validation,
+}
+
+pub fn init_api_token_keys(config: &Config) -> Result<Vec<ApiTokenKey>, AppError> {
+    config
+        .api_token_keys
+        .iter()
+        .map(|key| {
+            let decoding_key = match key.algorithm {
+                Algorithm::HS256 => DecodingKey::from_secret(key.secret.as_bytes()),
+                Algorithm::RS256 => DecodingKey::from_rsa_pem(key.secret.as_bytes())?,
+                Algorithm::EdDSA => DecodingKey::from_ed_pem(key.secret.as_bytes())?,
+                _ => {
+                    return Err(AppError::Config(format!(
+                        "Unsupported API token algorithm for key {}",
+                        key.kid
+                    )));
+                }
+            };
+
+            let mut validation = Validation::new(key.algorithm);
+            validation.set_audience(&[&config.api_token_audience]);
+            validation.set_required_spec_claims(&["exp", "iat", "aud"]);
+
+            Ok(ApiTokenKey {
+                kid: key.kid.clone(),
+                decoding_key,
+                validation,
+            })
+        })
+        .collect()
+}
+
+pub fn validate_jwt(keys: &[ApiTokenKey], jwt: &str) -> bool {
+    let header = match decode_header(jwt) {
+        Ok(header) => header,
+        Err(_) => return false,
+    };
+
+    let key = match &header.kid {
+        Some(kid) => keys.iter().find(|key| &key.kid == kid),
+        None => return false,
+    };
+
+    match key {
+        Some(key) => {
+            decode::<ApiClaims>(jwt, &key.decoding_key, &key.validation).is_ok_and(|data| {
+                data.claims.iat <= Utc::now().timestamp() + key.validation.leeway as i64
+                    && data.claims.exp - data.claims.iat <= MAX_API_TOKEN_LIFETIME_SECONDS
+            })
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde_json::json;
+
+    fn get_key(kid: &str, secret: &str) -> ApiTokenKey {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&["boilerswap-api"]);
+        validation.set_required_spec_claims(&["exp", "iat", "aud"]);
+
+        ApiTokenKey {
+            kid: kid.to_string(),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            validation,
+        }
+    }
+
+    fn get_jwt(kid: Option<&str>, secret: &str) -> String {
+        let now = Utc::now().timestamp();
+        let header = Header {
+            kid: kid.map(str::to_string),
+            ..Header::new(Algorithm::HS256)
+        };
+
+        encode(
+            &header,
+            &json!({ "aud": "boilerswap-api", "iat": now, "exp": now + 300 }),
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .expect("encode jwt")
+    }
+
+    #[test]
+    fn accepts_token_signed_by_its_kid() {
+        let keys = [get_key("current", "first"), get_key("next", "second")];
+
+        assert!(validate_jwt(&keys, &get_jwt(Some("current"), "first")));
+        assert!(validate_jwt(&keys, &get_jwt(Some("next"), "second")));
+    }
+
+    #[test]
+    fn rejects_token_without_kid() {
+        let keys = [get_key("current", "first")];
+
+        assert!(!validate_jwt(&keys, &get_jwt(None, "first")));
+    }
+
+    #[test]
+    fn rejects_token_with_unknown_or_mismatched_kid() {
+        let keys = [get_key("current", "first"), get_key("next", "second")];
+
+        assert!(!validate_jwt(&keys, &get_jwt(Some("retired"), "first")));
+        assert!(!validate_jwt(&keys, &get_jwt(Some("next"), "first")));
+    }
+}
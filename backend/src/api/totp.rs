@@ -0,0 +1,273 @@
+use super::{
+    database::get_totp_secret, models::RedisAction, redis::is_temporarily_locked,
+    twofactor::CODE_REGEX, verify::CODE_LENGTH,
+};
+use crate::{AppError, AppState};
+use aes_gcm::{
+    Aes256Gcm, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore},
+};
+use chrono::Utc;
+use data_encoding::{BASE32_NOPAD, BASE64};
+use hmac::{Hmac, Mac};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use rand::{RngCore, rngs::OsRng};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::warn;
+
+pub const TOTP_ISSUER: &str = "BoilerSwap";
+pub const TOTP_PERIOD_SECONDS: i64 = 30;
+pub const TOTP_SKEW_STEPS: i64 = 1;
+pub const TOTP_SECRET_BYTES: usize = 20;
+pub const NONCE_BYTES: usize = 12;
+
+const URI_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+pub fn generate_totp_secret() -> String {
+    let mut secret = [0u8; TOTP_SECRET_BYTES];
+
+    OsRng.fill_bytes(&mut secret);
+
+    BASE32_NOPAD.encode(&secret)
+}
+
+pub fn get_provisioning_uri(email: &str, secret: &str) -> String {
+    let issuer = utf8_percent_encode(TOTP_ISSUER, URI_COMPONENT);
+
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        issuer,
+        utf8_percent_encode(email, URI_COMPONENT),
+        secret,
+        issuer,
+        *CODE_LENGTH,
+        TOTP_PERIOD_SECONDS
+    )
+}
+
+fn get_cipher(key: &str) -> Aes256Gcm {
+    let digest = Sha256::digest(key.as_bytes());
+
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&digest))
+}
+
+pub fn encrypt_secret(key: &str, secret: &str) -> Result<String, AppError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = get_cipher(key)
+        .encrypt(&nonce, secret.as_bytes())
+        .map_err(|e| {
+            warn!("Failed to encrypt totp secret: {}", e);
+            AppError::Config(e.to_string())
+        })?;
+
+    Ok(BASE64.encode(&[nonce.as_slice(), &ciphertext].concat()))
+}
+
+pub fn decrypt_secret(key: &str, encrypted: &str) -> Result<String, AppError> {
+    let decoded = BASE64.decode(encrypted.as_bytes()).map_err(|e| {
+        warn!("Failed to decode totp secret: {}", e);
+        AppError::Config(e.to_string())
+    })?;
+
+    if decoded.len() <= NONCE_BYTES {
+        return Err(AppError::Config("Truncated totp secret".into()));
+    }
+
+    let (nonce, ciphertext) = decoded.split_at(NONCE_BYTES);
+
+    let plaintext = get_cipher(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|e| {
+            warn!("Failed to decrypt totp secret: {}", e);
+            AppError::Config(e.to_string())
+        })?;
+
+    Ok(String::from_utf8(plaintext)?)
+}
+
+fn generate_hotp(secret: &[u8], counter: u64) -> Result<String, AppError> {
+    let mut mac = <Hmac<Sha1> as Mac>::new_from_slice(secret).map_err(|e| {
+        warn!("Failed to create totp hmac: {}", e);
+        AppError::Config(e.to_string())
+    })?;
+
+    mac.update(&counter.to_be_bytes());
+
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    Ok(format!(
+        "{:0width$}",
+        binary % 10u32.pow(*CODE_LENGTH as u32),
+        width = *CODE_LENGTH
+    ))
+}
+
+pub fn check_totp(secret: &str, code: &str, timestamp: i64) -> Result<Option<i64>, AppError> {
+    if code.len() != *CODE_LENGTH || !CODE_REGEX.is_match(code) {
+        return Ok(None);
+    }
+
+    let decoded = BASE32_NOPAD.decode(secret.as_bytes()).map_err(|e| {
+        warn!("Failed to decode totp secret: {}", e);
+        AppError::Config(e.to_string())
+    })?;
+
+    let current_step = timestamp / TOTP_PERIOD_SECONDS;
+
+    for step in (current_step - TOTP_SKEW_STEPS)..=(current_step + TOTP_SKEW_STEPS) {
+        if generate_hotp(&decoded, step as u64)? == code {
+            return Ok(Some(step));
+        }
+    }
+
+    Ok(None)
+}
+
+pub async fn verify_totp(
+    state: Arc<AppState>,
+    email: &str,
+    secret: &str,
+    code: &str,
+) -> Result<bool, AppError> {
+    let step = match check_totp(secret, code, Utc::now().timestamp())? {
+        Some(step) => step,
+        None => return Ok(false),
+    };
+
+    Ok(!is_temporarily_locked(
+        state.clone(),
+        RedisAction::TotpUsed.as_ref(),
+        &format!("{}:{}", email, step),
+        TOTP_PERIOD_SECONDS * (2 * TOTP_SKEW_STEPS + 1),
+    )
+    .await?)
+}
+
+pub async fn verify_user_totp(
+    state: Arc<AppState>,
+    email: &str,
+    code: &str,
+) -> Result<bool, AppError> {
+    let encrypted = match get_totp_secret(state.clone(), email).await? {
+        Some(encrypted) => encrypted,
+        None => return Ok(false),
+    };
+
+    let secret = decrypt_secret(&state.config.totp_key, &encrypted)?;
+
+    verify_totp(state.clone(), email, &secret, code).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    // RFC 6238 Appendix B, SHA-1 column.
+    const RFC_VECTORS: [(i64, &str); 6] = [
+        (59, "94287082"),
+        (1111111109, "07081804"),
+        (1111111111, "14050471"),
+        (1234567890, "89005924"),
+        (2000000000, "69279037"),
+        (20000000000, "65353130"),
+    ];
+
+    fn truncate(vector: &str) -> &str {
+        &vector[vector.len() - *CODE_LENGTH..]
+    }
+
+    #[test]
+    fn matches_rfc_6238_vectors() {
+        let secret = BASE32_NOPAD.encode(RFC_SECRET);
+
+        for (timestamp, expected) in RFC_VECTORS {
+            let step = timestamp / TOTP_PERIOD_SECONDS;
+
+            assert_eq!(
+                generate_hotp(RFC_SECRET, step as u64).unwrap(),
+                truncate(expected)
+            );
+            assert_eq!(
+                check_totp(&secret, truncate(expected), timestamp).unwrap(),
+                Some(step)
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_only_adjacent_steps() {
+        let secret = BASE32_NOPAD.encode(RFC_SECRET);
+        let timestamp = 1234567890;
+        let step = timestamp / TOTP_PERIOD_SECONDS;
+
+        for offset in -TOTP_SKEW_STEPS..=TOTP_SKEW_STEPS {
+            let code = generate_hotp(RFC_SECRET, (step + offset) as u64).unwrap();
+
+            assert_eq!(
+                check_totp(&secret, &code, timestamp).unwrap(),
+                Some(step + offset)
+            );
+        }
+
+        for offset in [-TOTP_SKEW_STEPS - 1, TOTP_SKEW_STEPS + 1] {
+            let code = generate_hotp(RFC_SECRET, (step + offset) as u64).unwrap();
+
+            assert_eq!(check_totp(&secret, &code, timestamp).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        let secret = BASE32_NOPAD.encode(RFC_SECRET);
+
+        assert_eq!(check_totp(&secret, "12a456", 59).unwrap(), None);
+        assert_eq!(check_totp(&secret, "1", 59).unwrap(), None);
+    }
+
+    #[test]
+    fn round_trips_encrypted_secret() {
+        let secret = generate_totp_secret();
+        let encrypted = encrypt_secret("totp-key", &secret).unwrap();
+
+        assert_ne!(encrypted, secret);
+        assert_eq!(decrypt_secret("totp-key", &encrypted).unwrap(), secret);
+    }
+
+    #[test]
+    fn rejects_tampered_secret() {
+        let encrypted = encrypt_secret("totp-key", &generate_totp_secret()).unwrap();
+
+        let mut decoded = BASE64.decode(encrypted.as_bytes()).unwrap();
+        let last = decoded.len() - 1;
+        decoded[last] ^= 0x01;
+
+        assert!(decrypt_secret("totp-key", &BASE64.encode(&decoded)).is_err());
+        assert!(decrypt_secret("other-key", &encrypted).is_err());
+        assert!(decrypt_secret("totp-key", &BASE64.encode(&decoded[..NONCE_BYTES])).is_err());
+    }
+
+    #[test]
+    fn encodes_provisioning_label() {
+        let uri = get_provisioning_uri("first+last@purdue.edu", "ABC");
+
+        assert!(uri.starts_with("otpauth://totp/BoilerSwap:first%2Blast%40purdue.edu?secret=ABC&"));
+        assert!(uri.contains("&issuer=BoilerSwap&"));
+    }
+}
@@ -1,17 +1,16 @@
 use super::{
     database::get_user,
+    email::{log_email_error, send_email},
     redis::{increment_lock_key, is_redis_locked},
 };
 use crate::{AppError, AppState};
-use lettre::{
-    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
-    transport::smtp::authentication::Credentials,
-};
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
 use once_cell::sync::Lazy;
 use rand::{Rng, thread_rng};
 use regex::Regex;
+use sha2::Sha256;
 use std::sync::Arc;
-use tracing::{debug, warn};
 
 pub static CODE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d+$").unwrap());
 
@@ -21,29 +20,41 @@ pub fn generate_code() -> String {
     format!("{:06}", rng.gen_range(0..1_000_000))
 }
 
+fn get_code_mac(key: &str, code: &str) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_bytes())
+        .expect("hmac accepts keys of any length");
+
+    mac.update(code.as_bytes());
+
+    mac
+}
+
+pub fn hash_code(key: &str, code: &str) -> String {
+    HEXLOWER.encode(&get_code_mac(key, code).finalize().into_bytes())
+}
+
+pub fn verify_code(key: &str, code: &str, code_hash: &str) -> bool {
+    match HEXLOWER.decode(code_hash.as_bytes()) {
+        Ok(expected) => get_code_mac(key, code).verify_slice(&expected).is_ok(),
+        Err(_) => false,
+    }
+}
+
 async fn send_code_email(
     state: Arc<AppState>,
     user_email: &str,
     code: &str,
+    link: Option<String>,
 ) -> Result<(), AppError> {
-    let email = Message::builder()
-        .from(format!("BoilerSwap <{}>", state.config.from_email).parse()?)
-        .to(user_email.parse()?)
-        .subject("BoilerSwap Code")
-        .body(format!("Your code is {}", code))?;
-
-    let credentials = Credentials::new(
-        state.config.from_email.to_string(),
-        state.config.from_email_password.to_string(),
-    );
-
-    let mailer = AsyncSmtpTransport::<Tokio1Executor>::relay(&state.config.from_email_server)?
-        .credentials(credentials)
-        .build();
-
-    mailer.send(email).await?;
+    let body = match link {
+        Some(link) => format!(
+            "Your code is {}\n\nOr sign in with this link: {}",
+            code, link
+        ),
+        None => format!("Your code is {}", code),
+    };
 
-    Ok(())
+    send_email(state, user_email, "BoilerSwap Code", body).await
 }
 
 pub fn spawn_code_task(
@@ -51,6 +62,7 @@ pub fn spawn_code_task(
     email: String,
     token: String,
     forgot_key: Option<String>,
+    link: Option<String>,
 ) {
     tokio::spawn(async move {
         if forgot_key.is_some() {
@@ -74,18 +86,8 @@ pub fn spawn_code_task(
             }
         }
 
-        if let Err(error) = send_code_email(state.clone(), &email, &token).await {
-            match error {
-                AppError::LettreAddress(msg) => {
-                    debug!("Invalid email: {}", msg);
-                }
-                AppError::LettreTransport(msg) => {
-                    debug!("Transport error: {}", msg);
-                }
-                other => {
-                    warn!("Unexpected error: {:?}", other);
-                }
-            }
+        if let Err(error) = send_code_email(state.clone(), &email, &token, link).await {
+            log_email_error(error);
         } else if forgot_key.is_some()
             && (increment_lock_key(
                 state.clone(),
@@ -1,28 +1,140 @@
-use super::models::RedisAction;
-use axum::http::header::HeaderMap;
+use super::models::{RedisAction, SessionFingerprint};
+use crate::config::Config;
+use axum::http::header::{FORWARDED, HeaderMap, USER_AGENT};
+use ipnet::IpNet;
 use sha2::{Digest, Sha256};
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 
-pub fn get_hashed_ip(headers: &HeaderMap, direct_ip: IpAddr) -> String {
-    let ip = headers
-        .get("cf-connecting-ip")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string())
+pub fn get_hashed_ip(config: &Config, headers: &HeaderMap, direct_ip: IpAddr) -> String {
+    get_hash(&get_client_id(
+        &config.trusted_proxies,
+        &config.client_ip_headers,
+        headers,
+        direct_ip,
+    ))
+}
+
+fn get_client_id(
+    trusted_proxies: &[IpNet],
+    client_ip_headers: &[String],
+    headers: &HeaderMap,
+    direct_ip: IpAddr,
+) -> String {
+    if !is_trusted_proxy(trusted_proxies, direct_ip) {
+        return direct_ip.to_string();
+    }
+
+    for header in client_ip_headers {
+        let nodes = match header.as_str() {
+            "forwarded" => get_forwarded_nodes(headers),
+            _ => get_header_nodes(headers, header),
+        };
+
+        for node in nodes.iter().rev() {
+            match parse_node(node) {
+                Some(ip) if is_trusted_proxy(trusted_proxies, ip) => continue,
+                Some(ip) => return ip.to_string(),
+                None => return node.trim_matches('"').to_string(),
+            }
+        }
+
+        if let Some(first) = nodes.first().and_then(|node| parse_node(node)) {
+            return first.to_string();
+        }
+    }
+
+    direct_ip.to_string()
+}
+
+fn is_trusted_proxy(trusted_proxies: &[IpNet], ip: IpAddr) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    };
+
+    trusted_proxies.iter().any(|network| network.contains(&ip))
+}
+
+fn get_header_nodes<'a>(headers: &'a HeaderMap, name: &str) -> Vec<&'a str> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|s| s.split(','))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn get_forwarded_nodes(headers: &HeaderMap) -> Vec<&str> {
+    headers
+        .get_all(FORWARDED)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|s| s.split(','))
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.trim().split_once('=')?;
+                key.trim().eq_ignore_ascii_case("for").then(|| value.trim())
+            })
+        })
+        .collect()
+}
+
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim_matches('"');
+
+    node.parse::<IpAddr>()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|address| address.ip()))
         .or_else(|| {
-            headers
-                .get("x-forwarded-for")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.split(',').next().map(|s| s.trim().to_string()))
+            node.strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .and_then(|s| s.parse().ok())
         })
-        .unwrap_or_else(|| direct_ip.to_string());
+}
 
+pub fn get_hash(payload: &str) -> String {
     let mut hasher = Sha256::new();
 
-    hasher.update(ip.as_bytes());
+    hasher.update(payload.as_bytes());
 
     format!("{:x}", hasher.finalize())
 }
 
+pub fn get_device_label(headers: &HeaderMap) -> String {
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .iter()
+    .find(|(pattern, _)| user_agent.contains(pattern))
+    .map_or("Unknown browser", |(_, name)| name);
+
+    let platform = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("CrOS", "ChromeOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ]
+    .iter()
+    .find(|(pattern, _)| user_agent.contains(pattern))
+    .map_or("unknown device", |(_, name)| name);
+
+    format!("{} on {}", browser, platform)
+}
+
 pub fn get_key(redis_action: RedisAction, hashed_ip: &str) -> String {
     format!("{}:{}", redis_action.as_ref(), hashed_ip)
 }
@@ -30,3 +142,141 @@ pub fn get_key(redis_action: RedisAction, hashed_ip: &str) -> String {
 pub fn convert_i8_to_u8(payload: &i8) -> u8 {
     payload.checked_abs().unwrap_or(0) as u8
 }
+
+pub fn get_session_fingerprint(
+    config: &Config,
+    headers: &HeaderMap,
+    direct_ip: IpAddr,
+) -> SessionFingerprint {
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    SessionFingerprint {
+        device: get_device_label(headers),
+        user_agent_hash: get_hash(user_agent),
+        hashed_ips: vec![get_hashed_ip(config, headers, direct_ip)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const PROXY: &str = "10.0.0.2";
+
+    fn get_trusted_proxies() -> Vec<IpNet> {
+        vec!["10.0.0.0/8".parse().unwrap()]
+    }
+
+    fn get_headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+
+        headers
+    }
+
+    fn get_id(client_ip_headers: &[&str], pairs: &[(&'static str, &'static str)]) -> String {
+        let client_ip_headers: Vec<String> =
+            client_ip_headers.iter().map(|h| h.to_string()).collect();
+
+        get_client_id(
+            &get_trusted_proxies(),
+            &client_ip_headers,
+            &get_headers(pairs),
+            PROXY.parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn ignores_headers_from_untrusted_peers() {
+        let headers = get_headers(&[("x-forwarded-for", "203.0.113.7")]);
+
+        assert_eq!(
+            get_client_id(
+                &get_trusted_proxies(),
+                &["x-forwarded-for".to_string()],
+                &headers,
+                "198.51.100.1".parse().unwrap(),
+            ),
+            "198.51.100.1"
+        );
+    }
+
+    #[test]
+    fn uses_rightmost_untrusted_forwarded_for_hop() {
+        assert_eq!(
+            get_id(
+                &["x-forwarded-for"],
+                &[("x-forwarded-for", "1.1.1.1, 203.0.113.7, 10.0.0.5")]
+            ),
+            "203.0.113.7"
+        );
+    }
+
+    #[test]
+    fn ignores_forwarded_unless_configured() {
+        assert_eq!(
+            get_id(
+                &["x-forwarded-for"],
+                &[
+                    ("forwarded", "for=1.1.1.1"),
+                    ("x-forwarded-for", "203.0.113.7")
+                ]
+            ),
+            "203.0.113.7"
+        );
+    }
+
+    #[test]
+    fn stops_at_unparseable_hop() {
+        assert_eq!(
+            get_id(
+                &["x-forwarded-for"],
+                &[("x-forwarded-for", "203.0.113.7, unknown, 10.0.0.5")]
+            ),
+            "unknown"
+        );
+        assert_eq!(
+            get_id(
+                &["forwarded"],
+                &[(
+                    "forwarded",
+                    "for=203.0.113.7, for=_hidden, for=\"[::ffff:10.0.0.5]\""
+                )]
+            ),
+            "_hidden"
+        );
+    }
+
+    #[test]
+    fn reads_cf_connecting_ip_when_configured() {
+        assert_eq!(
+            get_id(
+                &["cf-connecting-ip", "x-forwarded-for"],
+                &[
+                    ("cf-connecting-ip", "203.0.113.9"),
+                    ("x-forwarded-for", "203.0.113.7")
+                ]
+            ),
+            "203.0.113.9"
+        );
+        assert_eq!(
+            get_id(
+                &["cf-connecting-ip", "x-forwarded-for"],
+                &[("x-forwarded-for", "203.0.113.7")]
+            ),
+            "203.0.113.7"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_peer_without_headers() {
+        assert_eq!(get_id(&["x-forwarded-for"], &[]), PROXY);
+    }
+}
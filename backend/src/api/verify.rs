@@ -1,53 +1,68 @@
 use super::{
-    models::{DummyClaims, RedisAction},
-    redis::try_get,
-    sessions::get_cookie,
+    csrf::{get_bearer_token, is_state_changing, verify_csrf},
+    models::{RedisAction, SessionCheck, VerificationQuestion},
+    password::{MAX_PASSWORD_LENGTH, PasswordRejections, check_password},
+    redis::{check_session_fingerprint, refresh_session, try_get},
+    sessions::{create_session_challenge, get_cookie},
+    similarity::normalize_text,
+    tokens::validate_jwt,
+    utilities::get_session_fingerprint,
 };
 use crate::{AppError, AppState};
 use argon2::{
     Algorithm::Argon2id, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier,
     Version::V0x13, password_hash::SaltString,
 };
-use axum::http::header::HeaderMap;
-use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{StatusCode, header::HeaderMap, request::Parts},
+    response::{IntoResponse, Response},
+};
 use once_cell::sync::Lazy;
 use rand::rngs::OsRng;
 use regex::Regex;
 use rustrict::CensorStr;
-use std::{env, fs::read_to_string, sync::Arc};
+use std::{
+    env,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+use tokio::task::spawn_blocking;
 use tracing::warn;
 
 pub static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^.+@purdue\.edu$").unwrap());
-pub static VALIDATION: Lazy<Validation> = Lazy::new(|| Validation::new(Algorithm::HS256));
-pub static DECODING_KEY: Lazy<DecodingKey> = Lazy::new(|| {
-    DecodingKey::from_secret(
-        read_to_string("/run/secrets/API_TOKEN")
-            .map(|s| s.trim().to_string())
-            .map_err(|e| {
-                warn!("Failed to read API_TOKEN from file: {}", e);
-                AppError::IO(e)
-            })
-            .unwrap()
-            .as_bytes(),
-    )
-});
 pub static MAX_CHARS: Lazy<usize> = Lazy::new(|| {
     env::var("PUBLIC_MAX_CHARS")
         .ok()
         .and_then(|val| val.parse::<usize>().ok())
         .unwrap_or(100)
 });
+pub const MAX_VERIFICATION_QUESTIONS: usize = 5;
 pub static CODE_LENGTH: Lazy<usize> = Lazy::new(|| {
     env::var("PUBLIC_CODE_LENGTH")
         .ok()
         .and_then(|val| val.parse::<usize>().ok())
         .unwrap_or(6)
 });
-pub static MIN_PASSWORD_LENGTH: Lazy<usize> = Lazy::new(|| {
-    env::var("PUBLIC_MIN_PASSWORD_LENGTH")
-        .ok()
-        .and_then(|val| val.parse::<usize>().ok())
-        .unwrap_or(10)
+pub static ARGON2_PARAMS: Lazy<Params> = Lazy::new(|| {
+    let get_param = |key: &str, default: u32| {
+        env::var(key)
+            .ok()
+            .and_then(|val| val.parse::<u32>().ok())
+            .unwrap_or(default)
+    };
+
+    Params::new(
+        get_param("RUST_ARGON2_MEMORY_KIB", 65536),
+        get_param("RUST_ARGON2_ITERATIONS", 3),
+        get_param("RUST_ARGON2_PARALLELISM", 1),
+        None,
+    )
+    .unwrap_or_else(|e| {
+        warn!("Invalid argon2 parameters, using default: {}", e);
+        Params::new(65536, 3, 1, None).expect("default argon2 parameters")
+    })
 });
 
 pub async fn verify_token(
@@ -85,14 +100,98 @@ pub async fn verify_token(
     Ok(None)
 }
 
-pub fn validate_api_token(headers: HeaderMap) -> bool {
-    let jwt = get_cookie(&headers, "api_token");
+pub enum SessionVerdict {
+    Valid(String),
+    Challenged(HeaderMap),
+    Invalid,
+}
+
+pub async fn verify_session(
+    state: Arc<AppState>,
+    headers: HeaderMap,
+    direct_ip: IpAddr,
+) -> Result<SessionVerdict, AppError> {
+    let fingerprint = get_session_fingerprint(&state.config, &headers, direct_ip);
 
-    if jwt.is_none() {
-        return false;
+    match verify_token(state.clone(), headers.clone()).await? {
+        Some((Some(email), RedisAction::Session, id))
+            if refresh_session(
+                state.clone(),
+                RedisAction::Session.as_ref(),
+                &id,
+                RedisAction::SessionStore.as_ref(),
+                &email,
+            )
+            .await? =>
+        {
+            match check_session_fingerprint(
+                state.clone(),
+                RedisAction::Session.as_ref(),
+                RedisAction::SessionStore.as_ref(),
+                &id,
+                &email,
+                fingerprint,
+            )
+            .await?
+            {
+                SessionCheck::Trusted => Ok(SessionVerdict::Valid(email)),
+                SessionCheck::Challenged => Ok(SessionVerdict::Challenged(
+                    create_session_challenge(state.clone(), &email, &headers, direct_ip).await?,
+                )),
+                SessionCheck::Revoked => Ok(SessionVerdict::Invalid),
+            }
+        }
+        _ => Ok(SessionVerdict::Invalid),
     }
+}
 
-    decode::<DummyClaims>(&jwt.expect("is_none failed"), &DECODING_KEY, &VALIDATION).is_ok()
+pub struct SessionUser {
+    pub email: String,
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for SessionUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let unauthorized = || (StatusCode::UNAUTHORIZED, "Invalid Credentials").into_response();
+
+        let direct_ip = match parts.extensions.get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(address)) => address.ip(),
+            None => return Err(unauthorized()),
+        };
+
+        if is_state_changing(&parts.method) && !verify_csrf(&state.config.csrf_key, &parts.headers)
+        {
+            return Err((StatusCode::FORBIDDEN, "Invalid CSRF Token").into_response());
+        }
+
+        match verify_session(state.clone(), parts.headers.clone(), direct_ip).await {
+            Ok(SessionVerdict::Valid(email)) => Ok(SessionUser { email }),
+            Ok(SessionVerdict::Challenged(headers)) => {
+                Err((StatusCode::UNAUTHORIZED, headers, "Verify your session").into_response())
+            }
+            Ok(SessionVerdict::Invalid) => Err(unauthorized()),
+            Err(e) => Err(e.into_response()),
+        }
+    }
+}
+
+pub fn validate_api_token(state: Arc<AppState>, headers: &HeaderMap) -> bool {
+    match get_cookie(headers, "api_token") {
+        Some(jwt) => validate_jwt(&state.api_token_keys, &jwt),
+        None => false,
+    }
+}
+
+pub fn validate_bearer_token(state: Arc<AppState>, headers: &HeaderMap) -> bool {
+    match get_bearer_token(headers) {
+        Some(jwt) => validate_jwt(&state.api_token_keys, jwt),
+        None => false,
+    }
 }
 
 pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, AppError> {
@@ -101,7 +200,7 @@ pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, AppE
         AppError::Config(e.to_string())
     })?;
 
-    Ok(Argon2::default()
+    Ok(get_argon2()
         .verify_password(password.as_bytes(), &parsed_hash)
         .is_ok())
 }
@@ -126,10 +225,28 @@ pub fn validate_item_attribute(payload: &str) -> Result<(), &'static str> {
     Ok(())
 }
 
+pub fn validate_verification(verification: &[VerificationQuestion]) -> Result<(), &'static str> {
+    if verification.is_empty() || verification.len() > MAX_VERIFICATION_QUESTIONS {
+        return Err("Between 1 and 5 verification questions");
+    }
+
+    for entry in verification {
+        validate_item_attribute(&entry.question)?;
+
+        validate_item_attribute(&entry.answer)?;
+
+        if normalize_text(&entry.answer).is_empty() {
+            return Err("Answer cannot be empty");
+        }
+    }
+
+    Ok(())
+}
+
 pub fn validate_account(email: &str, password: &str) -> Result<(), &'static str> {
     validate_email(email)?;
 
-    validate_password(password)?;
+    validate_password_length(password)?;
 
     Ok(())
 }
@@ -138,13 +255,23 @@ pub fn validate_length(payload: &str) -> bool {
     payload.len() < *MAX_CHARS
 }
 
-pub fn validate_password(password: &str) -> Result<(), &'static str> {
-    if !validate_length(password) && password.len() >= *MIN_PASSWORD_LENGTH {
+pub fn validate_password_length(password: &str) -> Result<(), &'static str> {
+    if password.is_empty() {
+        return Err("Password cannot be empty");
+    }
+
+    if password.chars().count() > *MAX_PASSWORD_LENGTH {
         return Err("Too many chars");
     }
 
-    if password.is_empty() {
-        return Err("Password cannot be empty");
+    Ok(())
+}
+
+pub fn validate_password(password: &str, email: Option<&str>) -> Result<(), PasswordRejections> {
+    let reasons = check_password(password, email);
+
+    if !reasons.is_empty() {
+        return Err(PasswordRejections { reasons });
     }
 
     Ok(())
@@ -162,17 +289,14 @@ pub fn validate_email(email: &str) -> Result<(), &'static str> {
     Ok(())
 }
 
+fn get_argon2() -> Argon2<'static> {
+    Argon2::new(Argon2id, V0x13, ARGON2_PARAMS.clone())
+}
+
 pub fn hash_password(password: &str) -> Result<String, AppError> {
     let salt = SaltString::generate(&mut OsRng);
 
-    let params = Params::new(65536, 3, 1, None).map_err(|e| {
-        warn!("Failed to hash password: {}", e);
-        AppError::Config(e.to_string())
-    })?;
-
-    let argon2 = Argon2::new(Argon2id, V0x13, params);
-
-    let password_hash = argon2
+    let password_hash = get_argon2()
         .hash_password(password.as_bytes(), &salt)
         .map_err(|e| {
             warn!("Failed to hash password: {}", e);
@@ -182,3 +306,34 @@ pub fn hash_password(password: &str) -> Result<String, AppError> {
 
     Ok(password_hash)
 }
+
+pub async fn spawn_hash_password(password: String) -> Result<String, AppError> {
+    spawn_blocking(move || hash_password(&password)).await?
+}
+
+pub async fn spawn_verify_password(
+    password: String,
+    password_hash: String,
+) -> Result<bool, AppError> {
+    spawn_blocking(move || verify_password(&password, &password_hash)).await?
+}
+
+pub fn needs_rehash(password_hash: &str) -> bool {
+    let parsed_hash = match PasswordHash::new(password_hash) {
+        Ok(parsed_hash) => parsed_hash,
+        Err(_) => return false,
+    };
+
+    if parsed_hash.algorithm != Argon2id.ident() || parsed_hash.version != Some(V0x13.into()) {
+        return true;
+    }
+
+    match Params::try_from(&parsed_hash) {
+        Ok(params) => {
+            params.m_cost() != ARGON2_PARAMS.m_cost()
+                || params.t_cost() != ARGON2_PARAMS.t_cost()
+                || params.p_cost() != ARGON2_PARAMS.p_cost()
+        }
+        Err(_) => true,
+    }
+}
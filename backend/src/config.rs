@@ -1,7 +1,24 @@
 use crate::error::AppError;
-use std::{env, fs::read_to_string};
+use ipnet::IpNet;
+use jsonwebtoken::Algorithm;
+use std::{env, fs::read_to_string, net::IpAddr};
 use tracing::{info, warn};
 
+#[derive(Debug, Clone)]
+pub struct ApiTokenKeyConfig {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub rust_port: u16,
@@ -9,16 +26,43 @@ pub struct Config {
     pub from_email: String,
     pub from_email_server: String,
     pub from_email_password: String,
+    pub totp_key: String,
+    pub magic_link_key: String,
+    pub code_key: String,
+    pub audit_key: String,
+    pub csrf_key: String,
+    pub api_token_keys: Vec<ApiTokenKeyConfig>,
+    pub api_token_audience: String,
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    pub oidc_allowed_domains: Vec<String>,
+    pub trusted_proxies: Vec<IpNet>,
+    pub client_ip_headers: Vec<String>,
     pub max_sessions: u8,
     pub auth_max_attempts: u8,
     pub auth_lock_duration_seconds: u16,
     pub verify_max_attempts: u8,
     pub verify_lock_duration_seconds: u16,
+    pub auth_ip_max_attempts: u8,
+    pub auth_account_max_attempts: u8,
+    pub verify_ip_max_attempts: u8,
+    pub verify_account_max_attempts: u8,
+    pub slow_down_after_attempts: u8,
+    pub slow_down_base_millis: u16,
+    pub slow_down_max_millis: u16,
     pub temporary_session_duration_seconds: u16,
     pub session_duration_seconds: u16,
+    pub session_max_duration_seconds: u32,
+    pub remember_session_duration_seconds: u32,
+    pub remember_session_max_duration_seconds: u32,
+    pub max_remembered_sessions: u8,
     pub max_codes: u8,
     pub max_codes_duration_seconds: u16,
     pub max_items: u8,
+    pub duplicate_similarity_percent: u8,
+    pub max_questions: u8,
+    pub max_questions_duration_seconds: u16,
+    pub found_item_duration_days: u8,
+    pub export_duration_seconds: u32,
 }
 
 impl Config {
@@ -69,6 +113,64 @@ impl Config {
             .parse()
             .map_err(|_| AppError::Config("Invalid RUST_VERIFY_LOCK_DURATION_SECS value".into()))?;
 
+        let auth_ip_max_attempts = var("RUST_AUTH_IP_MAX_ATTEMPTS")
+            .inspect_err(|_| {
+                info!("RUST_AUTH_IP_MAX_ATTEMPTS not set, using default");
+            })
+            .unwrap_or_else(|_| "60".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_AUTH_IP_MAX_ATTEMPTS value".into()))?;
+
+        let auth_account_max_attempts = var("RUST_AUTH_ACCOUNT_MAX_ATTEMPTS")
+            .inspect_err(|_| {
+                info!("RUST_AUTH_ACCOUNT_MAX_ATTEMPTS not set, using default");
+            })
+            .unwrap_or_else(|_| "30".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_AUTH_ACCOUNT_MAX_ATTEMPTS value".into()))?;
+
+        let verify_ip_max_attempts = var("RUST_VERIFY_IP_MAX_ATTEMPTS")
+            .inspect_err(|_| {
+                info!("RUST_VERIFY_IP_MAX_ATTEMPTS not set, using default");
+            })
+            .unwrap_or_else(|_| "30".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_VERIFY_IP_MAX_ATTEMPTS value".into()))?;
+
+        let verify_account_max_attempts = var("RUST_VERIFY_ACCOUNT_MAX_ATTEMPTS")
+            .inspect_err(|_| {
+                info!("RUST_VERIFY_ACCOUNT_MAX_ATTEMPTS not set, using default");
+            })
+            .unwrap_or_else(|_| "10".into())
+            .parse()
+            .map_err(|_| {
+                AppError::Config("Invalid RUST_VERIFY_ACCOUNT_MAX_ATTEMPTS value".into())
+            })?;
+
+        let slow_down_after_attempts = var("RUST_SLOW_DOWN_AFTER_ATTEMPTS")
+            .inspect_err(|_| {
+                info!("RUST_SLOW_DOWN_AFTER_ATTEMPTS not set, using default");
+            })
+            .unwrap_or_else(|_| "3".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_SLOW_DOWN_AFTER_ATTEMPTS value".into()))?;
+
+        let slow_down_base_millis = var("RUST_SLOW_DOWN_BASE_MS")
+            .inspect_err(|_| {
+                info!("RUST_SLOW_DOWN_BASE_MS not set, using default");
+            })
+            .unwrap_or_else(|_| "250".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_SLOW_DOWN_BASE_MS value".into()))?;
+
+        let slow_down_max_millis = var("RUST_SLOW_DOWN_MAX_MS")
+            .inspect_err(|_| {
+                info!("RUST_SLOW_DOWN_MAX_MS not set, using default");
+            })
+            .unwrap_or_else(|_| "8000".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_SLOW_DOWN_MAX_MS value".into()))?;
+
         let verify_max_attempts = var("RUST_VERIFY_MAX_ATTEMPTS")
             .inspect_err(|_| {
                 info!("RUST_VERIFY_MAX_ATTEMPTS not set, using default");
@@ -95,6 +197,55 @@ impl Config {
             .parse()
             .map_err(|_| AppError::Config("Invalid RUST_SESSION_DURATION_SECS value".into()))?;
 
+        let session_max_duration_seconds = var("RUST_SESSION_MAX_DURATION_SECS")
+            .inspect_err(|_| {
+                info!("RUST_SESSION_MAX_DURATION_SECS not set, using default");
+            })
+            .unwrap_or_else(|_| "43200".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_SESSION_MAX_DURATION_SECS value".into()))?;
+
+        let remember_session_duration_seconds = var("RUST_REMEMBER_SESSION_DURATION_SECS")
+            .inspect_err(|_| {
+                info!("RUST_REMEMBER_SESSION_DURATION_SECS not set, using default");
+            })
+            .unwrap_or_else(|_| "2592000".into())
+            .parse()
+            .map_err(|_| {
+                AppError::Config("Invalid RUST_REMEMBER_SESSION_DURATION_SECS value".into())
+            })?;
+
+        let remember_session_max_duration_seconds: u32 =
+            var("RUST_REMEMBER_SESSION_MAX_DURATION_SECS")
+                .inspect_err(|_| {
+                    info!("RUST_REMEMBER_SESSION_MAX_DURATION_SECS not set, using default");
+                })
+                .unwrap_or_else(|_| "7776000".into())
+                .parse()
+                .map_err(|_| {
+                    AppError::Config("Invalid RUST_REMEMBER_SESSION_MAX_DURATION_SECS value".into())
+                })?;
+
+        if remember_session_max_duration_seconds < remember_session_duration_seconds {
+            return Err(AppError::Config(
+                "RUST_REMEMBER_SESSION_MAX_DURATION_SECS cannot be less than RUST_REMEMBER_SESSION_DURATION_SECS".into(),
+            ));
+        }
+
+        let max_remembered_sessions: u8 = var("RUST_MAX_REMEMBERED_SESSIONS")
+            .inspect_err(|_| {
+                info!("RUST_MAX_REMEMBERED_SESSIONS not set, using default");
+            })
+            .unwrap_or_else(|_| "1".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_MAX_REMEMBERED_SESSIONS value".into()))?;
+
+        if max_remembered_sessions > max_sessions {
+            return Err(AppError::Config(
+                "RUST_MAX_REMEMBERED_SESSIONS cannot exceed RUST_MAX_SESSIONS".into(),
+            ));
+        }
+
         let max_codes = var("RUST_MAX_CODES")
             .inspect_err(|_| {
                 info!("RUST_MAX_CODES not set, using default");
@@ -119,6 +270,50 @@ impl Config {
             .parse()
             .map_err(|_| AppError::Config("Invalid RUST_MAX_ITEMS value".into()))?;
 
+        let duplicate_similarity_percent = var("RUST_DUPLICATE_SIMILARITY_PERCENT")
+            .inspect_err(|_| {
+                info!("RUST_DUPLICATE_SIMILARITY_PERCENT not set, using default");
+            })
+            .unwrap_or_else(|_| "85".into())
+            .parse()
+            .map_err(|_| {
+                AppError::Config("Invalid RUST_DUPLICATE_SIMILARITY_PERCENT value".into())
+            })?;
+
+        let max_questions = var("RUST_MAX_QUESTIONS")
+            .inspect_err(|_| {
+                info!("RUST_MAX_QUESTIONS not set, using default");
+            })
+            .unwrap_or_else(|_| "10".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_MAX_QUESTIONS value".into()))?;
+
+        let max_questions_duration_seconds = var("RUST_MAX_QUESTIONS_DURATION_SECS")
+            .inspect_err(|_| {
+                info!("RUST_MAX_QUESTIONS_DURATION_SECS not set, using default");
+            })
+            .unwrap_or_else(|_| "3600".into())
+            .parse()
+            .map_err(|_| {
+                AppError::Config("Invalid RUST_MAX_QUESTIONS_DURATION_SECS value".into())
+            })?;
+
+        let found_item_duration_days = var("RUST_FOUND_ITEM_DURATION_DAYS")
+            .inspect_err(|_| {
+                info!("RUST_FOUND_ITEM_DURATION_DAYS not set, using default");
+            })
+            .unwrap_or_else(|_| "30".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_FOUND_ITEM_DURATION_DAYS value".into()))?;
+
+        let export_duration_seconds = var("RUST_EXPORT_DURATION_SECS")
+            .inspect_err(|_| {
+                info!("RUST_EXPORT_DURATION_SECS not set, using default");
+            })
+            .unwrap_or_else(|_| "86400".into())
+            .parse()
+            .map_err(|_| AppError::Config("Invalid RUST_EXPORT_DURATION_SECS value".into()))?;
+
         let from_email = read_secret("RUST_FROM_EMAIL")
             .inspect_err(|_| {
                 info!("RUST_FROM_EMAIL not set, using default");
@@ -137,22 +332,173 @@ impl Config {
             })
             .unwrap_or_else(|_| "its so over".into());
 
+        let totp_key = read_secret("RUST_TOTP_KEY")
+            .inspect_err(|_| {
+                info!("RUST_TOTP_KEY not set, using default");
+            })
+            .unwrap_or_else(|_| "we need a real key".into());
+
+        let magic_link_key = read_secret("RUST_MAGIC_LINK_KEY")
+            .inspect_err(|_| {
+                info!("RUST_MAGIC_LINK_KEY not set, using default");
+            })
+            .unwrap_or_else(|_| "we need a real magic link key".into());
+
+        let code_key = read_secret("RUST_CODE_KEY")
+            .inspect_err(|_| {
+                info!("RUST_CODE_KEY not set, using default");
+            })
+            .unwrap_or_else(|_| "we need a real code key".into());
+
+        let audit_key = read_secret("RUST_AUDIT_KEY")
+            .inspect_err(|_| {
+                info!("RUST_AUDIT_KEY not set, using default");
+            })
+            .unwrap_or_else(|_| "we need a real audit key".into());
+
+        let csrf_key = read_secret("RUST_CSRF_KEY")
+            .inspect_err(|_| {
+                info!("RUST_CSRF_KEY not set, using default");
+            })
+            .unwrap_or_else(|_| "we need a real csrf key".into());
+
+        let api_token_keys = var("RUST_API_TOKEN_KEYS")
+            .inspect_err(|_| {
+                info!("RUST_API_TOKEN_KEYS not set, using default");
+            })
+            .unwrap_or_else(|_| "default:HS256:API_TOKEN".into())
+            .split(',')
+            .map(
+                |entry| match entry.trim().splitn(3, ':').collect::<Vec<_>>()[..] {
+                    [kid, algorithm, secret_name] => Ok(ApiTokenKeyConfig {
+                        kid: kid.to_string(),
+                        algorithm: algorithm.parse().map_err(|_| {
+                            AppError::Config("Invalid RUST_API_TOKEN_KEYS value".into())
+                        })?,
+                        secret: read_secret(secret_name)?,
+                    }),
+                    _ => Err(AppError::Config("Invalid RUST_API_TOKEN_KEYS value".into())),
+                },
+            )
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        if api_token_keys.is_empty() {
+            return Err(AppError::Config(
+                "RUST_API_TOKEN_KEYS cannot be empty".into(),
+            ));
+        }
+
+        let api_token_audience = var("RUST_API_TOKEN_AUDIENCE")
+            .inspect_err(|_| {
+                info!("RUST_API_TOKEN_AUDIENCE not set, using default");
+            })
+            .unwrap_or_else(|_| "boilerswap-api".into());
+
+        let oidc_providers = var("RUST_OIDC_PROVIDERS")
+            .inspect_err(|_| {
+                info!("RUST_OIDC_PROVIDERS not set, single sign-on disabled");
+            })
+            .unwrap_or_default()
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .map(
+                |entry| match entry.trim().split(';').collect::<Vec<_>>()[..] {
+                    [name, issuer, client_id] => Ok(OidcProviderConfig {
+                        name: name.to_string(),
+                        issuer: issuer.to_string(),
+                        client_id: client_id.to_string(),
+                        client_secret: None,
+                    }),
+                    [name, issuer, client_id, secret_name] => Ok(OidcProviderConfig {
+                        name: name.to_string(),
+                        issuer: issuer.to_string(),
+                        client_id: client_id.to_string(),
+                        client_secret: Some(read_secret(secret_name)?),
+                    }),
+                    _ => Err(AppError::Config("Invalid RUST_OIDC_PROVIDERS value".into())),
+                },
+            )
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let oidc_allowed_domains = var("RUST_OIDC_ALLOWED_DOMAINS")
+            .inspect_err(|_| {
+                info!("RUST_OIDC_ALLOWED_DOMAINS not set, using default");
+            })
+            .unwrap_or_else(|_| "purdue.edu".into())
+            .split(',')
+            .map(|domain| domain.trim().to_lowercase())
+            .filter(|domain| !domain.is_empty())
+            .collect();
+
+        let trusted_proxies = var("RUST_TRUSTED_PROXIES")
+            .inspect_err(|_| {
+                info!("RUST_TRUSTED_PROXIES not set, using default");
+            })
+            .unwrap_or_else(|_| "127.0.0.1/32,::1/128".into())
+            .split(',')
+            .map(|proxy| proxy.trim())
+            .filter(|proxy| !proxy.is_empty())
+            .map(|proxy| {
+                proxy
+                    .parse::<IpNet>()
+                    .or_else(|_| proxy.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| AppError::Config(format!("Invalid trusted proxy {}", proxy)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let client_ip_headers = var("RUST_CLIENT_IP_HEADERS")
+            .inspect_err(|_| {
+                info!("RUST_CLIENT_IP_HEADERS not set, using default");
+            })
+            .unwrap_or_else(|_| "x-forwarded-for".into())
+            .split(',')
+            .map(|header| header.trim().to_lowercase())
+            .filter(|header| !header.is_empty())
+            .collect();
+
         Ok(Self {
             rust_port,
             svelte_url,
             from_email,
             from_email_server,
             from_email_password,
+            totp_key,
+            magic_link_key,
+            code_key,
+            audit_key,
+            csrf_key,
+            api_token_keys,
+            api_token_audience,
+            oidc_providers,
+            oidc_allowed_domains,
+            trusted_proxies,
+            client_ip_headers,
             max_sessions,
             auth_max_attempts,
             auth_lock_duration_seconds,
             verify_max_attempts,
             verify_lock_duration_seconds,
+            auth_ip_max_attempts,
+            auth_account_max_attempts,
+            verify_ip_max_attempts,
+            verify_account_max_attempts,
+            slow_down_after_attempts,
+            slow_down_base_millis,
+            slow_down_max_millis,
             temporary_session_duration_seconds,
             session_duration_seconds,
+            session_max_duration_seconds,
+            remember_session_duration_seconds,
+            remember_session_max_duration_seconds,
+            max_remembered_sessions,
             max_codes,
             max_codes_duration_seconds,
             max_items,
+            duplicate_similarity_percent,
+            max_questions,
+            max_questions_duration_seconds,
+            found_item_duration_days,
+            export_duration_seconds,
         })
     }
 }
@@ -3,6 +3,7 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use jsonwebtoken::errors::Error as jwtError;
 use lettre::{
     address::AddressError, error::Error as lettreGeneralError,
     transport::smtp::Error as lettreTransportError,
@@ -10,6 +11,7 @@ use lettre::{
 use meilisearch_sdk::errors::Error as meiliError;
 use prometheus::Error as prometheusError;
 use redis::RedisError;
+use reqwest::Error as reqwestError;
 use scylla::{
     deserialize::DeserializationError,
     errors::{
@@ -23,6 +25,7 @@ use thiserror::Error;
 use tokio::task::JoinError;
 use tokio_cron_scheduler::JobSchedulerError;
 use tracing::error;
+use webauthn_rs::prelude::WebauthnError;
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -42,25 +45,25 @@ pub enum AppError {
     Prometheus(#[from] prometheusError),
 
     #[error("ScyllaDB new session error: {0}")]
-    ScyllaInit(#[from] NewSessionError),
+    ScyllaInit(Box<NewSessionError>),
 
     #[error("ScyllaDB execution error: {0}")]
-    ScyllaExecute(#[from] ExecutionError),
+    ScyllaExecute(Box<ExecutionError>),
 
     #[error("ScyllaDB prepare error: {0}")]
-    ScyllaPrepare(#[from] PrepareError),
+    ScyllaPrepare(Box<PrepareError>),
 
     #[error("ScyllaDB rows result error: {0}")]
-    ScyllaRowsResult(#[from] IntoRowsResultError),
+    ScyllaRowsResult(Box<IntoRowsResultError>),
 
     #[error("ScyllaDB first row error: {0}")]
-    ScyllaFirstRow(#[from] FirstRowError),
+    ScyllaFirstRow(Box<FirstRowError>),
 
     #[error("ScyllaDB row error: {0}")]
-    ScyllaRowsError(#[from] RowsError),
+    ScyllaRowsError(Box<RowsError>),
 
     #[error("ScyllaDB deserialization error: {0}")]
-    ScyllaDeserializationError(#[from] DeserializationError),
+    ScyllaDeserializationError(Box<DeserializationError>),
 
     #[error("Tokio join error: {0}")]
     TokioJoin(#[from] JoinError),
@@ -91,6 +94,57 @@ pub enum AppError {
 
     #[error("TokioCron error: {0}")]
     TokioCron(#[from] JobSchedulerError),
+
+    #[error("Webauthn error: {0}")]
+    Webauthn(#[from] WebauthnError),
+
+    #[error("JWT error: {0}")]
+    Jwt(#[from] jwtError),
+
+    #[error("HTTP client error: {0}")]
+    Reqwest(#[from] reqwestError),
+}
+
+impl From<NewSessionError> for AppError {
+    fn from(error: NewSessionError) -> Self {
+        AppError::ScyllaInit(Box::new(error))
+    }
+}
+
+impl From<ExecutionError> for AppError {
+    fn from(error: ExecutionError) -> Self {
+        AppError::ScyllaExecute(Box::new(error))
+    }
+}
+
+impl From<PrepareError> for AppError {
+    fn from(error: PrepareError) -> Self {
+        AppError::ScyllaPrepare(Box::new(error))
+    }
+}
+
+impl From<IntoRowsResultError> for AppError {
+    fn from(error: IntoRowsResultError) -> Self {
+        AppError::ScyllaRowsResult(Box::new(error))
+    }
+}
+
+impl From<FirstRowError> for AppError {
+    fn from(error: FirstRowError) -> Self {
+        AppError::ScyllaFirstRow(Box::new(error))
+    }
+}
+
+impl From<RowsError> for AppError {
+    fn from(error: RowsError) -> Self {
+        AppError::ScyllaRowsError(Box::new(error))
+    }
+}
+
+impl From<DeserializationError> for AppError {
+    fn from(error: DeserializationError) -> Self {
+        AppError::ScyllaDeserializationError(Box::new(error))
+    }
 }
 
 impl IntoResponse for AppError {
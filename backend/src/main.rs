@@ -1,11 +1,25 @@
 use crate::{
     api::{
+        csrf::CSRF_HEADER,
         database::start_cdc,
+        drafts::{delete_draft_handler, drafts_handler, post_draft_handler, publish_draft_handler},
+        found::{claim_handler, found_questions_handler, post_found_item_handler},
         handlers::{
-            api_token_check, authenticate_handler, delete_handler, forgot_handler,
-            post_item_handler, resend_handler, verify_handler,
+            account_deletion_code_handler, answer_handler, api_token_check, ask_handler,
+            authenticate_handler, change_email_handler, change_password_handler,
+            confirm_email_change_handler, delete_account_handler, delete_handler,
+            download_export_handler, export_handler, forgot_handler, notifications_handler,
+            post_item_handler, questions_handler, recovery_codes_handler, resend_handler,
+            revoke_all_sessions_handler, revoke_session_handler, sessions_handler,
+            totp_confirm_handler, totp_disable_handler, totp_enroll_handler,
+            update_notifications_handler, verify_handler,
         },
         models::RedisAction,
+        oidc::{oidc_finish_handler, oidc_start_handler},
+        passkeys::{
+            passkey_login_finish_handler, passkey_login_start_handler,
+            passkey_register_finish_handler, passkey_register_start_handler,
+        },
         schema::{KEYSPACE, columns::items, tables},
     },
     error::AppError,
@@ -15,7 +29,7 @@ use crate::{
 };
 use axum::{
     Router,
-    http::{Method, header::CONTENT_TYPE},
+    http::{HeaderName, Method, header::CONTENT_TYPE},
     middleware,
     routing::{delete, get, post},
 };
@@ -54,7 +68,7 @@ async fn main() -> Result<(), AppError> {
             origin.as_bytes() == origin_state.config.svelte_url.as_bytes()
         }))
         .allow_methods([Method::GET, Method::OPTIONS, Method::POST, Method::DELETE])
-        .allow_headers([CONTENT_TYPE])
+        .allow_headers([CONTENT_TYPE, HeaderName::from_static(CSRF_HEADER)])
         .max_age(Duration::from_secs(60 * 60));
 
     let app = Router::new()
@@ -64,6 +78,60 @@ async fn main() -> Result<(), AppError> {
         .route("/api/forgot", post(forgot_handler))
         .route("/api/post-item", post(post_item_handler))
         .route("/api/resend", post(resend_handler))
+        .route("/api/post-draft", post(post_draft_handler))
+        .route("/api/drafts", get(drafts_handler))
+        .route("/api/publish-draft", post(publish_draft_handler))
+        .route("/api/delete-draft", delete(delete_draft_handler))
+        .route("/api/questions/:item_id", get(questions_handler))
+        .route("/api/ask", post(ask_handler))
+        .route("/api/answer", post(answer_handler))
+        .route("/api/post-found-item", post(post_found_item_handler))
+        .route(
+            "/api/found-questions/:item_id",
+            get(found_questions_handler),
+        )
+        .route("/api/claim", post(claim_handler))
+        .route("/api/totp/enroll", post(totp_enroll_handler))
+        .route("/api/totp/confirm", post(totp_confirm_handler))
+        .route("/api/totp", delete(totp_disable_handler))
+        .route("/api/recovery-codes", post(recovery_codes_handler))
+        .route("/api/password", post(change_password_handler))
+        .route("/api/email", post(change_email_handler))
+        .route("/api/email/confirm", post(confirm_email_change_handler))
+        .route("/api/account", delete(delete_account_handler))
+        .route(
+            "/api/account/delete-code",
+            post(account_deletion_code_handler),
+        )
+        .route("/api/export", post(export_handler))
+        .route("/api/export/:export_id", get(download_export_handler))
+        .route(
+            "/api/sessions",
+            get(sessions_handler).delete(revoke_all_sessions_handler),
+        )
+        .route("/api/sessions/:id", delete(revoke_session_handler))
+        .route(
+            "/api/passkey/register/start",
+            post(passkey_register_start_handler),
+        )
+        .route(
+            "/api/passkey/register/finish",
+            post(passkey_register_finish_handler),
+        )
+        .route(
+            "/api/passkey/login/start",
+            post(passkey_login_start_handler),
+        )
+        .route(
+            "/api/passkey/login/finish",
+            post(passkey_login_finish_handler),
+        )
+        .route("/api/oidc/start", post(oidc_start_handler))
+        .route("/api/oidc/finish", post(oidc_finish_handler))
+        .route(
+            "/api/notifications",
+            get(notifications_handler).post(update_notifications_handler),
+        )
         .route("/metrics", get(metrics_handler))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
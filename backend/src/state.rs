@@ -2,7 +2,9 @@ use crate::{
     api::{
         database::{DatabaseQueries, expire_ttl, init_database, spawn_ttl_task},
         meilisearch::init_meilisearch,
+        passkeys::init_webauthn,
         redis::init_redis,
+        tokens::{ApiTokenKey, init_api_token_keys},
     },
     config::Config,
     error::AppError,
@@ -13,6 +15,7 @@ use redis::aio::ConnectionManager;
 use scylla::client::session::Session;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
+use webauthn_rs::Webauthn;
 
 pub struct AppState {
     pub config: Config,
@@ -21,6 +24,8 @@ pub struct AppState {
     pub database_queries: DatabaseQueries,
     pub redis_connection_manager: ConnectionManager,
     pub meili_client: Arc<Client>,
+    pub webauthn: Webauthn,
+    pub api_token_keys: Vec<ApiTokenKey>,
 }
 
 impl AppState {
@@ -28,27 +33,30 @@ impl AppState {
         let redis_future = init_redis();
         let (database_session, database_queries) = init_database().await?;
         let expire_ttl_now_future = expire_ttl(database_session.clone(), &database_queries);
-        let expire_ttl_future = spawn_ttl_task(database_session.clone(), &database_queries);
         let meili_future = init_meilisearch(database_session.clone(), &database_queries);
 
         let config = Config::load()?;
         let metrics = Metrics::default();
+        let webauthn = init_webauthn(&config.svelte_url)?;
+        let api_token_keys = init_api_token_keys(&config)?;
 
         let redis_connection_manager = redis_future.await?;
-        expire_ttl_future.await?;
         expire_ttl_now_future.await?;
         let (meili_client, meili_reindex_future) = meili_future.await?;
 
-        Ok((
-            Arc::new(Self {
-                config,
-                metrics,
-                database_session,
-                database_queries,
-                redis_connection_manager,
-                meili_client,
-            }),
-            meili_reindex_future,
-        ))
+        let state = Arc::new(Self {
+            config,
+            metrics,
+            database_session,
+            database_queries,
+            redis_connection_manager,
+            meili_client,
+            webauthn,
+            api_token_keys,
+        });
+
+        spawn_ttl_task(state.clone()).await?;
+
+        Ok((state, meili_reindex_future))
     }
 }
@@ -1,6 +1,16 @@
 import type { Handle } from '@sveltejs/kit'
 import { env } from '$env/dynamic/private'
-import { SignJWT } from 'jose'
+import { SignJWT, importPKCS8 } from 'jose'
+
+const apiTokenAlgorithm = env.API_TOKEN_ALG || 'HS256'
+
+async function getApiTokenKey() {
+	if (apiTokenAlgorithm === 'HS256') {
+		return new TextEncoder().encode(env.API_TOKEN)
+	}
+
+	return importPKCS8(env.API_TOKEN, apiTokenAlgorithm)
+}
 
 export const handle: Handle = async ({ event, resolve }) => {
 	if (
@@ -8,10 +18,11 @@ export const handle: Handle = async ({ event, resolve }) => {
 		!event.cookies.get('api_token')
 	) {
 		const jwt = await new SignJWT({})
-			.setProtectedHeader({ alg: 'HS256' })
+			.setProtectedHeader({ alg: apiTokenAlgorithm, kid: env.API_TOKEN_KID || 'default' })
+			.setAudience(env.API_TOKEN_AUDIENCE || 'boilerswap-api')
 			.setIssuedAt()
 			.setExpirationTime('5m')
-			.sign(new TextEncoder().encode(env.API_TOKEN))
+			.sign(await getApiTokenKey())
 
 		event.cookies.set('api_token', jwt, {
 			path: '/',
@@ -6,7 +6,7 @@ import {
 } from '$env/static/public'
 import { Status, type Account } from '$lib/models'
 import { appState } from '$lib/AppState.svelte'
-import { fetchBackend } from './utils'
+import { fetchBackend, getCsrfHeaders } from './utils'
 
 export async function forgot(email: string): Promise<void> {
 	if (appState.getLimited()) {
@@ -108,6 +108,24 @@ export async function verify(auth_code: string): Promise<void> {
 	} catch (err) {}
 }
 
+export async function verifyMagicLink(token: string): Promise<void> {
+	const response = await fetch(PUBLIC_BACKEND_URL + '/verify', {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json', ...getCsrfHeaders() },
+		credentials: 'include',
+		body: JSON.stringify({ token })
+	})
+
+	if (!response.ok) {
+		appState.setAuthError((await response.text()).slice(0, 50))
+		goto('/auth')
+		return
+	}
+
+	appState.setStatus(Status.isSignedIn, true)
+	goto('/browse')
+}
+
 export async function verify_forget(auth_code: string) {
 	if (appState.getLimited()) {
 		return
@@ -173,6 +191,7 @@ export async function signout() {
 
 	const response = await fetch(PUBLIC_BACKEND_URL + '/delete', {
 		method: 'DELETE',
+		headers: getCsrfHeaders(),
 		credentials: 'include'
 	})
 
@@ -195,6 +214,7 @@ export async function resend(resendSeconds: number) {
 
 	const response = await fetch(PUBLIC_BACKEND_URL + '/resend', {
 		method: 'POST',
+		headers: getCsrfHeaders(),
 		credentials: 'include'
 	})
 
@@ -203,3 +223,43 @@ export async function resend(resendSeconds: number) {
 		throw new Error(`HTTP error! status: ${response.status}`)
 	}
 }
+
+export async function oidcStart(provider: string, remember: boolean = false) {
+	if (appState.getLimited()) {
+		return
+	}
+
+	appState.nowLimited()
+
+	const response = await fetch(PUBLIC_BACKEND_URL + '/oidc/start', {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json', ...getCsrfHeaders() },
+		credentials: 'include',
+		body: JSON.stringify({ provider, remember })
+	})
+
+	if (!response.ok) {
+		appState.setAuthError((await response.text()).slice(0, 50))
+		throw new Error(`HTTP error! status: ${response.status}`)
+	}
+
+	window.location.href = (await response.json()).url
+}
+
+export async function oidcFinish(code: string, state: string) {
+	const response = await fetch(PUBLIC_BACKEND_URL + '/oidc/finish', {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json', ...getCsrfHeaders() },
+		credentials: 'include',
+		body: JSON.stringify({ code, state })
+	})
+
+	if (!response.ok) {
+		appState.setAuthError((await response.text()).slice(0, 50))
+		goto('/auth')
+		return
+	}
+
+	appState.setStatus(Status.isSignedIn, true)
+	goto('/browse')
+}
@@ -3,11 +3,21 @@ import { appState } from '$lib/AppState.svelte'
 import type { Account, ExpirationColor, TokenPayload } from '../models'
 import DOMPurify from 'dompurify'
 
+export function getCsrfHeaders(): Record<string, string> {
+	const token = document.cookie
+		.split('; ')
+		.find((cookie) => cookie.startsWith('csrf_token='))
+		?.split('=')[1]
+
+	return token ? { 'x-csrf-token': token } : {}
+}
+
 export async function fetchBackend(path: string, payload: Account | TokenPayload) {
 	const response = await fetch(PUBLIC_BACKEND_URL + path, {
 		method: 'POST',
 		headers: {
-			'Content-Type': 'application/json'
+			'Content-Type': 'application/json',
+			...getCsrfHeaders()
 		},
 		credentials: 'include',
 		body: JSON.stringify(payload)
@@ -43,3 +53,24 @@ export function sanitizeHtml(input: string): string {
 		ALLOWED_ATTR: []
 	})
 }
+
+export async function downloadExport(exportId: string): Promise<boolean> {
+	const response = await fetch(PUBLIC_BACKEND_URL + '/export/' + encodeURIComponent(exportId), {
+		credentials: 'include'
+	})
+
+	if (!response.ok) {
+		return false
+	}
+
+	const url = URL.createObjectURL(await response.blob())
+	const link = document.createElement('a')
+
+	link.href = url
+	link.download = 'boilerswap-export.json'
+	link.click()
+
+	URL.revokeObjectURL(url)
+
+	return true
+}